@@ -0,0 +1,337 @@
+use std::{
+    io::{self, Read},
+    path::Path,
+    process,
+};
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::{
+    data::{self, Format},
+    projects::ProjectEntry,
+    prompt, validate,
+};
+
+/// Asks for every field of a new entry, following the schema of its category so that new
+/// fields are asked for without changes here
+pub fn interactive<T: ProjectEntry + DeserializeOwned>(data_dir: &str) -> T {
+    let schema = schemars::schema_for!(T);
+    let definitions = schema
+        .get("$defs")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+
+    println!("Fields marked (optional) can be left empty");
+    let entry = ask_object("", schema.as_value(), &definitions);
+
+    let entry: T = serde_json::from_value(entry).unwrap_or_else(|e| exit_with(&[e.to_string()]));
+    check(entry, data_dir)
+}
+
+/// Reads a new entry from a TOML, YAML or JSON file, or from stdin if the path is `-`
+pub fn from_file<T: ProjectEntry + DeserializeOwned>(path: &Path, data_dir: &str) -> T {
+    let entry: Result<T, String> = if path == Path::new("-") {
+        let mut contents = String::new();
+        io::stdin()
+            .read_to_string(&mut contents)
+            .expect("Unable to read from stdin");
+        let format = if contents.trim_start().starts_with('{') {
+            Format::Json
+        } else {
+            Format::Toml
+        };
+        format.parse(&contents)
+    } else {
+        data::read(path)
+    };
+
+    check(entry.unwrap_or_else(|e| exit_with(&[e])), data_dir)
+}
+
+/// Checks the entry about to be written to `data_dir`, whose registry has to hold the authors
+/// it references for the data to still load
+fn check<T: ProjectEntry>(entry: T, data_dir: &str) -> T {
+    let mut errors = entry.validate();
+    let registry: Vec<Option<String>> =
+        data::read_raw_registry(data::AUTHORS, data_dir, &mut errors)
+            .into_iter()
+            .map(|author| author.id)
+            .collect();
+    for author in entry.authors() {
+        if author.is_unresolved() && !registry.contains(&author.id) {
+            errors.push(format!(
+                "unknown author {}, add it to the author registry first",
+                author.id.as_deref().unwrap_or_default()
+            ));
+        }
+    }
+    if !errors.is_empty() {
+        exit_with(&errors);
+    }
+    entry
+}
+
+fn exit_with(errors: &[String]) -> ! {
+    eprintln!("The entry is not valid:");
+    for error in errors {
+        eprintln!("  - {}", error);
+    }
+    process::exit(1);
+}
+
+fn ask_object(path: &str, schema: &Value, definitions: &Map<String, Value>) -> Value {
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let mut object = Map::new();
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for (name, property) in properties.into_iter().flatten() {
+        let path = match path {
+            "" => name.clone(),
+            _ => format!("{}.{}", path, name),
+        };
+        if let Some(value) = ask_field(&path, property, definitions, required.contains(&&**name)) {
+            object.insert(name.clone(), value);
+        }
+    }
+
+    Value::Object(object)
+}
+
+fn ask_field(
+    path: &str,
+    property: &Value,
+    definitions: &Map<String, Value>,
+    required: bool,
+) -> Option<Value> {
+    let description = property.get("description").and_then(Value::as_str);
+    let (property, nullable) = resolve(property, definitions);
+    let optional = !required || nullable;
+
+    let mut hints: Vec<String> = description.map(str::to_string).into_iter().collect();
+    let items = property.get("items").map(|i| resolve(i, definitions).0);
+    let variants = items
+        .unwrap_or(property)
+        .get("enum")
+        .and_then(Value::as_array);
+    // Descriptions of enums already list their variants
+    if let (None, Some(variants)) = (description, variants) {
+        hints.push(format!("one of {}", join_variants(variants)));
+    }
+    if optional {
+        hints.push("optional".to_string());
+    }
+    let label = if hints.is_empty() {
+        format!("{}:", path)
+    } else {
+        format!("{} ({}):", path, hints.join(", "))
+    };
+
+    match type_of(property) {
+        // Leaving an optional boolean empty keeps it unknown rather than false
+        "boolean" if optional => ask_optional_yes_no(&label).map(Value::Bool),
+        "boolean" => Some(Value::Bool(ask_yes_no(&label))),
+        "object" => {
+            if optional && !ask_yes_no(&format!("Fill in {}?", path)) {
+                return None;
+            }
+            Some(ask_object(path, property, definitions))
+        }
+        "array" => {
+            let (items, _) = resolve(property.get("items")?, definitions);
+            if type_of(items) == "object" {
+                let reference = string_variant(property.get("items")?, definitions);
+                let mut values = vec![];
+                while ask_yes_no(&format!("Add an entry to {}?", path)) {
+                    // Entries that can be written as a string, e.g. an author given by its id
+                    // in the registry, are filled in only if that is left empty
+                    if let Some(reference) = reference {
+                        let description = reference
+                            .get("description")
+                            .and_then(Value::as_str)
+                            .unwrap_or("id");
+                        let answer = prompt::ask(&format!(
+                            "{} ({}, leave empty to fill it in):",
+                            path, description
+                        ));
+                        if !answer.trim().is_empty() {
+                            values.push(Value::String(answer.trim().to_string()));
+                            continue;
+                        }
+                    }
+                    values.push(ask_object(path, items, definitions));
+                }
+                return Some(Value::Array(values));
+            }
+
+            loop {
+                let answer = prompt::ask(&format!("{} comma-separated list", label));
+                let values: Result<Vec<Value>, String> = answer
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                    .map(|v| parse_scalar(path, v, items))
+                    .collect();
+                match values {
+                    Ok(values) => return Some(Value::Array(values)),
+                    Err(e) => println!("{}", e),
+                }
+            }
+        }
+        _ => loop {
+            let answer = if property.get("x-multiline").is_some() {
+                ask_multiline(&label)
+            } else {
+                prompt::ask(&label).trim().to_string()
+            };
+
+            if answer.is_empty() {
+                if optional {
+                    return None;
+                }
+                println!("{} is required", path);
+                continue;
+            }
+
+            match parse_scalar(path, &answer, property) {
+                Ok(value) => return Some(value),
+                Err(e) => println!("{}", e),
+            }
+        },
+    }
+}
+
+/// Follows references to definitions, and unwraps the optional ones
+fn resolve<'a>(property: &'a Value, definitions: &'a Map<String, Value>) -> (&'a Value, bool) {
+    if let Some(variants) = property.get("anyOf").and_then(Value::as_array) {
+        let nullable = variants.iter().any(|v| type_of(v) == "null");
+        if let Some(variant) = variants.iter().find(|v| type_of(v) != "null") {
+            let (resolved, _) = resolve(variant, definitions);
+            return (resolved, nullable);
+        }
+    }
+
+    let nullable = property
+        .get("type")
+        .and_then(Value::as_array)
+        .is_some_and(|types| types.iter().any(|t| t == "null"));
+
+    match property
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|r| r.strip_prefix("#/$defs/"))
+        .and_then(|name| definitions.get(name))
+    {
+        // Definitions can themselves be references or alternatives, e.g. `Author`
+        Some(definition) => {
+            let (resolved, definition_nullable) = resolve(definition, definitions);
+            (resolved, nullable || definition_nullable)
+        }
+        None => (property, nullable),
+    }
+}
+
+/// Alternative of a property that is written as a string, e.g. the id of an author
+fn string_variant<'a>(
+    property: &'a Value,
+    definitions: &'a Map<String, Value>,
+) -> Option<&'a Value> {
+    let property = match property
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|r| r.strip_prefix("#/$defs/"))
+    {
+        Some(name) => definitions.get(name)?,
+        None => property,
+    };
+    property
+        .get("anyOf")?
+        .as_array()?
+        .iter()
+        .find(|variant| type_of(variant) == "string")
+}
+
+/// The main type of a property, ignoring `null`
+fn type_of(property: &Value) -> &str {
+    match property.get("type") {
+        Some(Value::String(t)) => t,
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|t| *t != "null")
+            .unwrap_or("null"),
+        _ if property.get("enum").is_some() => "string",
+        _ => "object",
+    }
+}
+
+fn parse_scalar(path: &str, answer: &str, property: &Value) -> Result<Value, String> {
+    if let Some(variants) = property.get("enum").and_then(Value::as_array) {
+        return variants
+            .iter()
+            .find(|v| v.as_str() == Some(answer))
+            .cloned()
+            .ok_or_else(|| format!("{} is not one of {}", answer, join_variants(variants)));
+    }
+
+    match type_of(property) {
+        "integer" => answer
+            .parse::<i64>()
+            .map(Value::from)
+            .map_err(|_| format!("{} is not a number", answer)),
+        _ => {
+            validate::field(path, answer)?;
+            Ok(Value::String(answer.to_string()))
+        }
+    }
+}
+
+fn ask_multiline(label: &str) -> String {
+    println!("{} end with a line containing a single '.'", label);
+    let mut lines = vec![];
+    loop {
+        let line = prompt::ask(">");
+        if line == "." {
+            break;
+        }
+        lines.push(line);
+    }
+
+    if lines.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", lines.join("\n"))
+    }
+}
+
+/// Unlike `prompt::confirm`, also reads the answer when the input is piped
+fn ask_yes_no(question: &str) -> bool {
+    let answer = prompt::ask(&format!("{} [y/N]", question));
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Like `ask_yes_no`, but an empty answer is no answer
+fn ask_optional_yes_no(question: &str) -> Option<bool> {
+    loop {
+        let answer = prompt::ask(&format!("{} [y/n]", question));
+        match answer.trim().to_lowercase().as_str() {
+            "" => return None,
+            "y" | "yes" => return Some(true),
+            "n" | "no" => return Some(false),
+            _ => println!("Answer y or n, or leave it empty"),
+        }
+    }
+}
+
+fn join_variants(variants: &[Value]) -> String {
+    variants
+        .iter()
+        .filter_map(Value::as_str)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,132 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+
+use crate::projects::{Author, ProjectEntry, Projects};
+
+/// Version of the author index format, to bump whenever a field is removed or changes meaning.
+/// See `doc/author-index.md` for the description of the format.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Machine-readable document listing the projects of each author across all the categories
+#[derive(Serialize, Debug)]
+pub struct AuthorIndex {
+    pub version: u32,
+    pub authors: Vec<IndexedAuthor>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct IndexedAuthor {
+    /// Id in the author registry, or else Matrix ID, or else name
+    pub key: String,
+    pub name: String,
+    pub matrix_id: Option<String>,
+    pub website: Option<String>,
+    pub organisation: Option<String>,
+    pub projects: Vec<IndexedProject>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct IndexedProject {
+    pub slug: String,
+    pub category: &'static str,
+    pub title: String,
+    pub matrixdotorg_url: String,
+}
+
+impl AuthorIndex {
+    pub fn new(projects: &Projects) -> Self {
+        // Authors written in full in an entry are the same person as the registry author
+        // with their Matrix ID
+        let registry_ids = projects
+            .authors
+            .iter()
+            .filter_map(|(id, details)| Some((details.matrix_id.clone()?, id.clone())))
+            .collect();
+
+        let mut authors = BTreeMap::new();
+        push_all(&mut authors, &registry_ids, &projects.bots);
+        push_all(&mut authors, &registry_ids, &projects.bridges);
+        push_all(&mut authors, &registry_ids, &projects.clients);
+        push_all(&mut authors, &registry_ids, &projects.iots);
+        push_all(&mut authors, &registry_ids, &projects.others);
+        push_all(&mut authors, &registry_ids, &projects.sdks);
+        push_all(&mut authors, &registry_ids, &projects.servers);
+
+        // The registry has the last word on the details of its authors
+        for (id, details) in &projects.authors {
+            if let Some(indexed) = authors.get_mut(id) {
+                indexed.name = details.name.clone();
+                indexed.matrix_id = details.matrix_id.clone();
+                indexed.website = details.website.clone();
+                indexed.organisation = details.organisation.clone();
+            }
+        }
+
+        AuthorIndex {
+            version: FORMAT_VERSION,
+            authors: authors.into_values().collect(),
+        }
+    }
+
+    /// Renders the index, with authors sorted by key and their projects by category then
+    /// slug so that it can be diffed across runs
+    pub fn to_json(&self) -> String {
+        let mut json =
+            serde_json::to_string_pretty(self).expect("Unable to serialize author index");
+        json.push('\n');
+        json
+    }
+}
+
+fn push_all<T: ProjectEntry>(
+    authors: &mut BTreeMap<String, IndexedAuthor>,
+    registry_ids: &HashMap<String, String>,
+    entries: &[T],
+) {
+    let mut entries: Vec<&T> = entries.iter().collect();
+    entries.sort_by_key(|entry| entry.slug());
+
+    for entry in entries {
+        let project = IndexedProject {
+            slug: entry.slug(),
+            category: T::CATEGORY,
+            title: entry.title().to_string(),
+            matrixdotorg_url: entry.matrixdotorg_url(),
+        };
+
+        for author in entry.authors() {
+            let key = key_of(author, registry_ids);
+            let indexed = authors.entry(key.clone()).or_insert_with(|| IndexedAuthor {
+                key,
+                name: author.name.clone(),
+                matrix_id: None,
+                website: None,
+                organisation: None,
+                projects: vec![],
+            });
+            // Entries don't all give the same details about an author
+            indexed.matrix_id = indexed.matrix_id.take().or(author.matrix_id.clone());
+            indexed.website = indexed.website.take().or(author.website.clone());
+            indexed.organisation = indexed.organisation.take().or(author.organisation.clone());
+
+            // An author listed twice in the same entry is indexed once
+            if indexed.projects.last() != Some(&project) {
+                indexed.projects.push(project.clone());
+            }
+        }
+    }
+}
+
+fn key_of(author: &Author, registry_ids: &HashMap<String, String>) -> String {
+    if let Some(id) = &author.id {
+        return id.clone();
+    }
+    match &author.matrix_id {
+        Some(matrix_id) => registry_ids
+            .get(matrix_id)
+            .cloned()
+            .unwrap_or_else(|| matrix_id.clone()),
+        None => author.name.clone(),
+    }
+}
@@ -1,30 +1,164 @@
 use convert_case::{Case, Casing};
-use indoc::formatdoc;
+use indoc::{formatdoc, indoc};
 use itertools::Itertools;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::projects::Author;
+use crate::projects::{self, Author, Maturity, ProjectEntry, ProjectLink};
+use crate::validate;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// Command the bot answers to
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct Command {
+    /// As typed in the room, e.g. "!beep"
+    pub command: String,
+    pub description: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct Bot {
     pub title: String,
+    /// One-line summary of the project
     pub description: String,
     pub authors: Vec<Author>,
+    /// Can be Obsolete, Alpha, Beta or Stable
+    #[schemars(with = "Maturity")]
     pub maturity: String,
     pub language: String,
+    /// Has to be one of https://spdx.org/licenses/
     pub license: String,
     pub repository: Option<String>,
     pub home: Option<String>,
     pub screenshot: Option<String>,
     pub icon: Option<String>,
+    /// Alias of the room to discuss the project
     pub room: Option<String>,
+    /// SDKs the project is built on, slugs or titles of SDK entries
+    #[serde(default)]
+    pub sdk: Vec<String>,
+    /// Pages of the SDKs of `sdk` that are in the data
+    #[serde(skip)]
+    pub sdk_links: Vec<ProjectLink>,
     pub featured: bool,
     pub sort_order: Option<i32>,
+    /// Emoji the TWIM bot uses for the project
+    pub twim_emoji: Option<String>,
+    /// Matrix ID of a public instance of the bot anyone can invite
+    pub instance: Option<String>,
+    /// Power level the bot needs in a room to work, e.g. 50 to kick users
+    pub power_level: Option<i64>,
+    /// Whether the bot works in encrypted rooms
+    pub e2ee: Option<bool>,
+    #[serde(default)]
+    pub commands: Vec<Command>,
+    /// Multi-line description, supports markdown
+    #[schemars(extend("x-multiline" = true))]
     pub full_description: String,
 }
 
-impl Bot {
-    pub fn to_markdown(&self) -> String {
+impl ProjectEntry for Bot {
+    const CATEGORY: &'static str = "bot";
+    const KEY: &'static str = "bots";
+    const DIRECTORY: &'static str = "bots";
+
+    fn example() -> Self {
+        Bot {
+            title: "Botty".to_string(),
+            description: "Botty likes to do beep sounds whenever someone says \"beep bop\""
+                .to_string(),
+            authors: vec![
+                Author {
+                    name: "Fred".to_string(),
+                    matrix_id: Some("@fred:server.tld".to_string()),
+                    ..Default::default()
+                },
+                Author {
+                    name: "The rest of the Botty team".to_string(),
+                    matrix_id: None,
+                    ..Default::default()
+                },
+            ],
+            maturity: "Beta".to_string(),
+            language: "Python".to_string(),
+            license: "AGPL-3.0-or-later".to_string(),
+            repository: Some("https://git.server.tld/botty".to_string()),
+            home: Some("https://botty.tld".to_string()),
+            screenshot: Some("/data/botty-screenshot.png".to_string()),
+            icon: Some("/data/botty-icon.svg".to_string()),
+            room: Some("#botty:server.tld".to_string()),
+            sdk: vec!["matrix-nio".to_string()],
+            sdk_links: vec![],
+            featured: false,
+            sort_order: Some(1),
+            twim_emoji: Some("🤖".to_string()),
+            instance: Some("@botty:server.tld".to_string()),
+            power_level: Some(0),
+            e2ee: Some(true),
+            commands: vec![
+                Command {
+                    command: "!beep".to_string(),
+                    description: "Botty answers with a bop".to_string(),
+                },
+                Command {
+                    command: "!love".to_string(),
+                    description: "Tells botty it's loved".to_string(),
+                },
+            ],
+            full_description: indoc! {"
+                A multi-line description that supports markdown to describe the project extensively.
+
+                For example, botty can:
+                * boot
+                * beep
+                * be outraged if you don't say \"beep bop\" at least once a week
+
+                It's _very important_ for botty to feel loved.
+            "}
+            .to_string(),
+        }
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn authors(&self) -> &[Author] {
+        &self.authors
+    }
+
+    fn authors_mut(&mut self) -> &mut Vec<Author> {
+        &mut self.authors
+    }
+
+    fn license(&self) -> &str {
+        &self.license
+    }
+
+    fn room(&self) -> Option<&str> {
+        self.room.as_deref()
+    }
+
+    fn sdk(&self) -> &[String] {
+        &self.sdk
+    }
+
+    fn set_sdk_links(&mut self, links: Vec<ProjectLink>) {
+        self.sdk_links = links;
+    }
+
+    fn sort_order(&self) -> Option<i32> {
+        self.sort_order
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut errors = validate::entry(self);
+        if let Some(Err(e)) = self.instance.as_deref().map(validate::matrix_id) {
+            errors.push(e);
+        }
+        errors
+    }
+
+    fn to_markdown(&self) -> String {
         let layout = match self.icon {
             Some(_) => "projectimage",
             None => "project",
@@ -46,8 +180,20 @@ impl Bot {
             self.home.as_ref().map(|h| format!("home: {h}")),
             self.screenshot.as_ref().map(|s| format!("screenshot: {s}")),
             self.icon.as_ref().map(|i| format!("thumbnail: {i}")),
-            self.room.as_ref().map(|r| format!("room: \"{r}\"")),
+            self.room
+                .as_deref()
+                .map(|r| format!("room: {}", projects::front_matter_string(r))),
             self.sort_order.as_ref().map(|o| format!("sort_order: {o}")),
+            projects::front_matter_list("sdk", &self.sdk),
+            ProjectLink::front_matter("sdk_links", &self.sdk_links),
+            self.instance
+                .as_deref()
+                .map(|i| format!("instance: {}", projects::front_matter_string(i))),
+            self.power_level
+                .as_ref()
+                .map(|p| format!("power_level: {p}")),
+            self.e2ee.as_ref().map(|e| format!("e2ee: {e}")),
+            commands_front_matter(&self.commands),
         ]
         .iter()
         .flatten()
@@ -73,7 +219,25 @@ impl Bot {
         "}
     }
 
-    pub fn filename(&self) -> String {
+    fn filename(&self) -> String {
         format!("{}.mdx", self.title.to_case(Case::Kebab))
     }
 }
+
+/// Front matter of the commands of the bot, or nothing if it has none
+fn commands_front_matter(commands: &[Command]) -> Option<String> {
+    if commands.is_empty() {
+        return None;
+    }
+    let items = commands
+        .iter()
+        .map(|c| {
+            format!(
+                "    - command: {}\n      description: {}",
+                projects::front_matter_string(&c.command),
+                projects::front_matter_string(&c.description)
+            )
+        })
+        .join("\n");
+    Some(format!("commands:\n{}", items))
+}
@@ -1,31 +1,202 @@
 use convert_case::{Case, Casing};
-use indoc::formatdoc;
+use indoc::{formatdoc, indoc};
 use itertools::Itertools;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::projects::Author;
+use crate::comparison::FeatureLevels;
+use crate::projects::{self, Author, Maturity, ProjectEntry, ProjectLink, SupportLevel};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// Support of each feature by a bridge, unknown when missing
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, Default)]
+pub struct Features {
+    /// Users of the network appear as Matrix users
+    pub puppeting: Option<SupportLevel>,
+    /// Messages sent from the network by a Matrix user appear as sent by their Matrix account
+    pub double_puppeting: Option<SupportLevel>,
+    /// Matrix users without an account on the network can talk through a bot
+    pub relay_mode: Option<SupportLevel>,
+    pub e2ee: Option<SupportLevel>,
+    pub media: Option<SupportLevel>,
+    pub reactions: Option<SupportLevel>,
+    pub edits: Option<SupportLevel>,
+    pub threads: Option<SupportLevel>,
+    pub direct_messages: Option<SupportLevel>,
+    pub group_chats: Option<SupportLevel>,
+    /// Past messages of the network are imported
+    pub backfill: Option<SupportLevel>,
+}
+
+impl FeatureLevels for Features {
+    fn levels(&self) -> Vec<(&'static str, Option<SupportLevel>)> {
+        let Features {
+            puppeting,
+            double_puppeting,
+            relay_mode,
+            e2ee,
+            media,
+            reactions,
+            edits,
+            threads,
+            direct_messages,
+            group_chats,
+            backfill,
+        } = self;
+
+        vec![
+            ("Puppeting", *puppeting),
+            ("Double puppeting", *double_puppeting),
+            ("Relay mode", *relay_mode),
+            ("E2EE", *e2ee),
+            ("Media", *media),
+            ("Reactions", *reactions),
+            ("Edits", *edits),
+            ("Threads", *threads),
+            ("Direct messages", *direct_messages),
+            ("Group chats", *group_chats),
+            ("Backfill", *backfill),
+        ]
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct Bridge {
     pub title: String,
+    /// One-line summary of the project
     pub description: String,
     pub authors: Vec<Author>,
+    /// Can be Obsolete, Alpha, Beta or Stable
+    #[schemars(with = "Maturity")]
     pub maturity: String,
     pub language: String,
+    /// Has to be one of https://spdx.org/licenses/
     pub license: String,
     pub repository: Option<String>,
     pub home: Option<String>,
     pub screenshot: Option<String>,
     pub icon: Option<String>,
+    /// Alias of the room to discuss the project
     pub room: Option<String>,
+    /// SDKs the project is built on, slugs or titles of SDK entries
+    #[serde(default)]
+    pub sdk: Vec<String>,
+    /// Pages of the SDKs of `sdk` that are in the data
+    #[serde(skip)]
+    pub sdk_links: Vec<ProjectLink>,
     pub featured: bool,
     pub sort_order: Option<i32>,
+    /// Emoji the TWIM bot uses for the project
+    pub twim_emoji: Option<String>,
+    /// Networks the bridge connects Matrix to, ids, names or aliases of the protocol registry
     pub bridges: Vec<String>,
+    /// Names of the networks of `bridges`, as in the protocol registry when they are in it
+    #[serde(skip)]
+    pub networks: Vec<String>,
+    /// Pages of the protocols of `bridges` that are in the registry
+    #[serde(skip)]
+    pub protocol_links: Vec<ProjectLink>,
+    /// Support of each feature, can be "yes", "no" or "partial"
+    pub features: Option<Features>,
+    /// Multi-line description, supports markdown
+    #[schemars(extend("x-multiline" = true))]
     pub full_description: String,
 }
 
-impl Bridge {
-    pub fn to_markdown(&self) -> String {
+impl ProjectEntry for Bridge {
+    const CATEGORY: &'static str = "bridge";
+    const KEY: &'static str = "bridges";
+    const DIRECTORY: &'static str = "bridges";
+
+    fn example() -> Self {
+        Bridge {
+            title: "Bridgeridoo".to_string(),
+            description: "Brigeridoo bridges the Ridoo platform to Matrix".to_string(),
+            authors: vec![
+                Author {
+                    name: "Fred".to_string(),
+                    matrix_id: Some("@fred:server.tld".to_string()),
+                    ..Default::default()
+                },
+                Author {
+                    name: "Bridgey Inc.".to_string(),
+                    matrix_id: None,
+                    ..Default::default()
+                },
+            ],
+            maturity: "Stable".to_string(),
+            language: "Python".to_string(),
+            license: "AGPL-3.0-or-later".to_string(),
+            repository: Some("https://git.server.tld/bridgey".to_string()),
+            home: Some("https://bridgey.tld".to_string()),
+            screenshot: Some("/data/bridgey-screenshot.png".to_string()),
+            icon: Some("/data/bridgey-icon.svg".to_string()),
+            room: Some("#bridgey:server.tld".to_string()),
+            sdk: vec!["mautrix-go".to_string()],
+            sdk_links: vec![],
+            featured: false,
+            sort_order: Some(1),
+            twim_emoji: Some("🌉".to_string()),
+            bridges: vec!["Ridoo".to_string()],
+            networks: vec![],
+            protocol_links: vec![],
+            features: Some(Features {
+                puppeting: Some(SupportLevel::Yes),
+                double_puppeting: Some(SupportLevel::Yes),
+                relay_mode: Some(SupportLevel::No),
+                e2ee: Some(SupportLevel::Partial),
+                media: Some(SupportLevel::Yes),
+                reactions: Some(SupportLevel::Yes),
+                edits: Some(SupportLevel::Yes),
+                threads: Some(SupportLevel::No),
+                direct_messages: Some(SupportLevel::Yes),
+                group_chats: Some(SupportLevel::Yes),
+                backfill: Some(SupportLevel::Partial),
+            }),
+            full_description: indoc! {"
+                A multi-line description that supports markdown to describe the project extensively.
+
+                For example, bridgey can:
+                * open DMs
+                * participate in conversations
+                * send automatic replies
+            "}
+            .to_string(),
+        }
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn authors(&self) -> &[Author] {
+        &self.authors
+    }
+
+    fn authors_mut(&mut self) -> &mut Vec<Author> {
+        &mut self.authors
+    }
+
+    fn license(&self) -> &str {
+        &self.license
+    }
+
+    fn room(&self) -> Option<&str> {
+        self.room.as_deref()
+    }
+
+    fn sdk(&self) -> &[String] {
+        &self.sdk
+    }
+
+    fn set_sdk_links(&mut self, links: Vec<ProjectLink>) {
+        self.sdk_links = links;
+    }
+
+    fn sort_order(&self) -> Option<i32> {
+        self.sort_order
+    }
+
+    fn to_markdown(&self) -> String {
         let layout = match self.icon {
             Some(_) => "projectimage",
             None => "project",
@@ -41,7 +212,12 @@ impl Bridge {
         let language = &self.language;
         let license = &self.license;
         let featured = &self.featured;
-        let bridges = self.bridges.iter().format(", ");
+        let networks = if self.networks.is_empty() {
+            &self.bridges
+        } else {
+            &self.networks
+        };
+        let bridges = networks.iter().format(", ");
 
         let optional_fields = [
             self.repository.as_ref().map(|r| format!("repo: {r}")),
@@ -50,6 +226,12 @@ impl Bridge {
             self.icon.as_ref().map(|i| format!("thumbnail: {i}")),
             self.room.as_ref().map(|r| format!("room: \"{r}\"")),
             self.sort_order.as_ref().map(|o| format!("sort_order: {o}")),
+            projects::front_matter_list("sdk", &self.sdk),
+            ProjectLink::front_matter("sdk_links", &self.sdk_links),
+            ProjectLink::front_matter("protocol_links", &self.protocol_links),
+            self.features
+                .as_ref()
+                .and_then(|f| projects::features_front_matter(&f.levels())),
         ]
         .iter()
         .flatten()
@@ -76,7 +258,7 @@ impl Bridge {
         "}
     }
 
-    pub fn filename(&self) -> String {
+    fn filename(&self) -> String {
         format!("{}.mdx", self.title.to_case(Case::Kebab))
     }
 }
@@ -1,12 +1,14 @@
 use convert_case::{Case, Casing};
-use indoc::formatdoc;
+use indoc::{formatdoc, indoc};
 use itertools::Itertools;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::projects::Author;
 use crate::projects::Maturity;
+use crate::projects::{self, Author, MscStatus, MscSupport, ProjectEntry, ProjectLink};
+use crate::validate;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct Features {
     pub e2ee: String,
     pub widgets: String,
@@ -26,13 +28,13 @@ pub struct Features {
     pub localised: String,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct AppStoreDetails {
     pub org: String,
     pub app_id: String,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Platform {
     Linux,
     Android,
@@ -43,37 +45,230 @@ pub enum Platform {
     MobileWeb,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct Client {
+    /// Ideally UpperCamel case name of the project, shared by its clients for each platform
     pub id: String,
     pub title: String,
     pub slug: Option<String>,
+    /// One-line summary of the project
     pub description: String,
     pub authors: Vec<Author>,
+    /// Can be Obsolete, Alpha, Beta or Stable
     pub maturity: Maturity,
     pub language: String,
+    /// Has to be one of https://spdx.org/licenses/
     pub license: String,
     pub repository: Option<String>,
     pub home: Option<String>,
     pub screenshot: Option<String>,
     pub icon: Option<String>,
+    /// Alias of the room to discuss the project
     pub room: Option<String>,
+    /// Mandatory but can be empty, slugs or titles of SDK entries
     pub sdk: Vec<String>,
+    /// Pages of the SDKs of `sdk` that are in the data
+    #[serde(skip)]
+    pub sdk_links: Vec<ProjectLink>,
     pub platforms: Vec<Platform>,
     pub featured: bool,
     pub sort_order: Option<i32>,
+    /// Emoji the TWIM bot uses for the project
+    pub twim_emoji: Option<String>,
+    /// Support of each feature, can be "yes", "no" or "partial"
     pub features: Features,
+    /// Versions of the Matrix spec the client implements, e.g. "v1.11" or "r0.6.1"
+    #[serde(default)]
+    pub spec_versions: Vec<String>,
+    /// Matrix Spec Changes the client implements, with status "planned", "in_progress", "partial" or "implemented"
+    #[serde(default)]
+    pub msc_support: Vec<MscSupport>,
     pub appstore_details: Option<AppStoreDetails>,
     pub apple_associated_app_id: Option<String>,
     pub playstore_app_id: Option<String>, // e.g. "im.vector.app"
     pub fdroid_app_id: Option<String>,    // e.g. "im.vector.app"
     pub flathub_app_id: Option<String>,   // e.g. ""
     pub otherinstall_link: Option<String>, // e.g. = ["https://element.io/get-started"]
+    /// Multi-line description, supports markdown
+    #[schemars(extend("x-multiline" = true))]
     pub full_description: String,
 }
 
-impl Client {
-    pub fn to_markdown(&self) -> String {
+impl ProjectEntry for Client {
+    const CATEGORY: &'static str = "client";
+    const KEY: &'static str = "clients";
+    const DIRECTORY: &'static str = "clients";
+
+    fn example() -> Self {
+        Client {
+            id: "ClientFromTheFuture".to_string(),
+            title: "Client From the Future".to_string(),
+            slug: Some("client-from-the-future".to_string()),
+            description: "A client that supports MSCs that haven't been written yet.".to_string(),
+            authors: vec![
+                Author {
+                    name: "Marty McBee".to_string(),
+                    matrix_id: Some("@marty:server.tld".to_string()),
+                    ..Default::default()
+                },
+                Author {
+                    name: "The Physician".to_string(),
+                    matrix_id: None,
+                    ..Default::default()
+                },
+            ],
+            maturity: Maturity::Alpha,
+            language: "TypeScript".to_string(),
+            license: "AGPL-3.0-only".to_string(),
+            repository: Some("https://git.clientfromthefuture.tld/cftf".to_string()),
+            home: Some("https://clientfromthefuture.tld".to_string()),
+            screenshot: Some("/data/cftf-screenshot.png".to_string()),
+            icon: Some("/data/cftf-icon.svg".to_string()),
+            room: Some("#clientfromthefuture:server.tld".to_string()),
+            sdk: vec!["matrix-js-sdk".to_string()],
+            sdk_links: vec![],
+            platforms: vec![
+                Platform::Linux,
+                Platform::MacOS,
+                Platform::Windows,
+                Platform::DesktopWeb,
+                Platform::MobileWeb,
+                Platform::Android,
+                Platform::Ios,
+            ],
+            featured: false,
+            sort_order: Some(1),
+            twim_emoji: Some("🔮".to_string()),
+            features: Features {
+                e2ee: "yes".to_string(),
+                widgets: "yes".to_string(),
+                spaces: "yes".to_string(),
+                room_directory: "yes".to_string(),
+                read_receipts: "yes".to_string(),
+                typing_indicators: "yes".to_string(),
+                edits: "yes".to_string(),
+                replies: "yes".to_string(),
+                threads: "yes".to_string(),
+                attachments: "yes".to_string(),
+                multi_account: "no".to_string(),
+                registration: "yes".to_string(),
+                calls: "yes".to_string(),
+                reactions: "yes".to_string(),
+                sso: "yes".to_string(),
+                localised: "yes".to_string(),
+            },
+            spec_versions: vec!["v1.11".to_string()],
+            msc_support: vec![MscSupport {
+                msc: "MSC3575".to_string(),
+                status: MscStatus::Implemented,
+            }],
+            appstore_details: Some(AppStoreDetails {
+                org: "clientinc".to_string(),
+                app_id: "id1234567890".to_string(),
+            }),
+            apple_associated_app_id: Some("ABCDE12345.tld.clientfromthefuture.cftf".to_string()),
+            playstore_app_id: Some("tld.clientfromthefuture.cftf".to_string()),
+            fdroid_app_id: Some("tld.clientfromthefuture.cftf".to_string()),
+            flathub_app_id: Some("tld.clientfromthefuture.cftf".to_string()),
+            otherinstall_link: Some("https://clientfromthefuture.tld/install".to_string()),
+            full_description: indoc! {"
+                A multi-line description to describe how great Client From the Future is.
+
+                CFTF goes beyond being a regular Matrix client: it traveled through time to
+                bring all the awesomeness from the next decades.
+            "}
+            .to_string(),
+        }
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn authors(&self) -> &[Author] {
+        &self.authors
+    }
+
+    fn authors_mut(&mut self) -> &mut Vec<Author> {
+        &mut self.authors
+    }
+
+    fn license(&self) -> &str {
+        &self.license
+    }
+
+    fn room(&self) -> Option<&str> {
+        self.room.as_deref()
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let Features {
+            e2ee,
+            widgets,
+            spaces,
+            room_directory,
+            read_receipts,
+            typing_indicators,
+            edits,
+            replies,
+            threads,
+            attachments,
+            multi_account,
+            registration,
+            calls,
+            reactions,
+            sso,
+            localised,
+        } = &self.features;
+        let levels = [
+            e2ee,
+            widgets,
+            spaces,
+            room_directory,
+            read_receipts,
+            typing_indicators,
+            edits,
+            replies,
+            threads,
+            attachments,
+            multi_account,
+            registration,
+            calls,
+            reactions,
+            sso,
+            localised,
+        ];
+
+        let mut errors = validate::entry(self);
+        errors.extend(
+            levels
+                .iter()
+                .filter_map(|l| validate::feature_level(l).err()),
+        );
+        errors.extend(validate::spec_support(
+            &self.spec_versions,
+            &self.msc_support,
+        ));
+        errors
+    }
+
+    fn sdk(&self) -> &[String] {
+        &self.sdk
+    }
+
+    fn set_sdk_links(&mut self, links: Vec<ProjectLink>) {
+        self.sdk_links = links;
+    }
+
+    fn msc_support(&self) -> &[MscSupport] {
+        &self.msc_support
+    }
+
+    fn sort_order(&self) -> Option<i32> {
+        self.sort_order
+    }
+
+    fn to_markdown(&self) -> String {
         let layout = match self.icon {
             Some(_) => "projectimage",
             None => "project",
@@ -132,6 +327,9 @@ impl Client {
             self.icon.as_ref().map(|i| format!("thumbnail: {i}")),
             self.room.as_ref().map(|r| format!("room: \"{r}\"")),
             self.sort_order.as_ref().map(|o| format!("sort_order: {o}")),
+            ProjectLink::front_matter("sdk_links", &self.sdk_links),
+            projects::front_matter_list("spec_versions", &self.spec_versions),
+            projects::msc_support_front_matter(&self.msc_support),
         ]
         .iter()
         .flatten()
@@ -176,7 +374,7 @@ impl Client {
         "}
     }
 
-    pub fn filename(&self) -> String {
+    fn filename(&self) -> String {
         let normalised_name: String = self
             .title
             .to_case(Case::Kebab)
@@ -190,6 +388,14 @@ impl Client {
         format!("{}.mdx", normalised_name)
     }
 
+    fn slug(&self) -> String {
+        self.slug
+            .clone()
+            .unwrap_or_else(|| self.filename().trim_end_matches(".mdx").to_string())
+    }
+}
+
+impl Client {
     pub fn matrixto_filename(&self) -> String {
         self.id
             .chars()
@@ -0,0 +1,104 @@
+use itertools::Itertools;
+
+use crate::projects::SupportLevel;
+
+/// Directory of the matrix.org pages comparing or indexing the projects of a category, next
+/// to the directories of the categories
+pub const DIRECTORY: &str = "comparisons";
+
+/// Value of a cell the project doesn't give
+const UNKNOWN: &str = "unknown";
+
+/// Features of the projects of a category, compared on matrix.org. Implementations
+/// destructure the features so that a new field can't be left out of the pages.
+pub trait FeatureLevels: Default {
+    /// Label of each feature, as shown on matrix.org, with its support
+    fn levels(&self) -> Vec<(&'static str, Option<SupportLevel>)>;
+}
+
+/// Column of a feature table: a project with its features, and the values of the rows shown
+/// before and after them
+pub struct Column<'a, F> {
+    pub title: &'a str,
+    pub features: Option<&'a F>,
+    pub before: Vec<(&'static str, Option<String>)>,
+    pub after: Vec<(&'static str, Option<String>)>,
+}
+
+impl<'a, F> Column<'a, F> {
+    pub fn new(title: &'a str, features: Option<&'a F>) -> Self {
+        Column {
+            title,
+            features,
+            before: vec![],
+            after: vec![],
+        }
+    }
+}
+
+/// Markdown table of the support of each feature by each project, along with the rows of
+/// the columns
+pub fn features_table<F: FeatureLevels>(columns: &[Column<F>]) -> String {
+    let Some(first) = columns.first() else {
+        return String::new();
+    };
+
+    let labels = first
+        .before
+        .iter()
+        .map(|(label, _)| *label)
+        .chain(F::default().levels().into_iter().map(|(label, _)| label))
+        .chain(first.after.iter().map(|(label, _)| *label))
+        .collect::<Vec<_>>();
+    let projects = columns
+        .iter()
+        .map(|column| {
+            let levels = match column.features {
+                Some(features) => features.levels(),
+                None => F::default().levels(),
+            };
+            let values = column
+                .before
+                .iter()
+                .map(|(_, value)| value.clone())
+                .chain(levels.into_iter().map(|(_, l)| l.map(|l| l.to_string())))
+                .chain(column.after.iter().map(|(_, value)| value.clone()))
+                .collect();
+            (column.title, values)
+        })
+        .collect::<Vec<_>>();
+    table(&labels, &projects)
+}
+
+/// Markdown table with a row per label and a column per project, given as its title and its
+/// value for each label
+fn table(labels: &[&str], projects: &[(&str, Vec<Option<String>>)]) -> String {
+    let header = format!(
+        "| Feature | {} |\n| --- |{}",
+        projects.iter().map(|(title, _)| title).join(" | "),
+        " --- |".repeat(projects.len())
+    );
+    let rows = labels.iter().enumerate().map(|(index, label)| {
+        let cells = projects
+            .iter()
+            .map(|(_, values)| {
+                values
+                    .get(index)
+                    .cloned()
+                    .flatten()
+                    .unwrap_or_else(|| UNKNOWN.to_string())
+            })
+            .join(" | ");
+        format!("| {} | {} |", label, cells)
+    });
+
+    std::iter::once(header).chain(rows).join("\n")
+}
+
+/// matrix.org page comparing the projects of a category
+pub fn page(title: &str, category: &str, table: &str) -> String {
+    format!(
+        "---\nlayout: comparison\ntitle: {}\ncategories:\n - {}\n---\n{}\n",
+        title, category, table
+    )
+}
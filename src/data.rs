@@ -0,0 +1,864 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use itertools::Itertools;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value as JsonValue};
+
+use crate::{
+    bot::Bot,
+    bridge::Bridge,
+    client::Client,
+    iot::Iot,
+    migrate,
+    other::Other,
+    output::Output,
+    projects::{AuthorDetails, ProjectEntry, Projects},
+    protocol::Protocol,
+    sdk::Sdk,
+    server::Server,
+    templates,
+};
+
+/// Name, without extension, of the single file listing every project, from before the
+/// projects were split in one file each
+pub const LEGACY_FILE_STEM: &str = "projects";
+
+/// Entries shared by the projects and referenced by their id, written in a file of the data
+/// directory with a table per entry (e.g. `authors.toml`) or in a table of the legacy single file
+#[derive(Clone, Copy, Debug)]
+pub struct Registry {
+    /// Name of the file, without extension, and key of the table in the legacy single file
+    pub name: &'static str,
+    /// What each entry is, for the messages
+    pub kind: &'static str,
+}
+
+pub const AUTHORS: Registry = Registry {
+    name: "authors",
+    kind: "author",
+};
+
+pub const PROTOCOLS: Registry = Registry {
+    name: "protocols",
+    kind: "protocol",
+};
+
+const REGISTRIES: [Registry; 2] = [AUTHORS, PROTOCOLS];
+
+/// Formats the data files can be written in, detected by their extension
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [Format::Toml, Format::Yaml, Format::Json];
+
+    pub fn from_path(path: &Path) -> Option<Format> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Some(Format::Toml),
+            Some("yaml" | "yml") => Some(Format::Yaml),
+            Some("json") => Some(Format::Json),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Toml => "toml",
+            Format::Yaml => "yaml",
+            Format::Json => "json",
+        }
+    }
+
+    pub fn parse<T: DeserializeOwned>(self, contents: &str) -> Result<T, String> {
+        match self {
+            Format::Toml => toml::from_str(contents).map_err(|e| e.to_string()),
+            Format::Yaml => serde_yaml::from_str(contents).map_err(|e| e.to_string()),
+            Format::Json => serde_json::from_str(contents).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Renders a single entry in its own file, TOML entries are laid out the way they are
+    /// written by hand
+    pub fn render_entry<T: ProjectEntry>(self, entry: &T) -> String {
+        match self {
+            Format::Toml => format!(
+                "{} = {}\n\n{}",
+                migrate::VERSION_KEY,
+                migrate::CURRENT_VERSION,
+                templates::standalone_entry(entry)
+            ),
+            _ => {
+                let mut value = serde_json::to_value(entry).expect("Unable to serialize entry");
+                if let Some(object) = value.as_object_mut() {
+                    object.shift_insert(
+                        0,
+                        migrate::VERSION_KEY.to_string(),
+                        migrate::CURRENT_VERSION.into(),
+                    );
+                }
+                self.render(&value)
+            }
+        }
+    }
+
+    /// Renders a registry in its own file
+    fn render_registry<T: Serialize>(self, entries: &BTreeMap<String, T>) -> String {
+        match self {
+            Format::Toml => format!(
+                "{} = {}\n\n{}",
+                migrate::VERSION_KEY,
+                migrate::CURRENT_VERSION,
+                templates::registry(entries, None)
+            ),
+            _ => {
+                let mut value =
+                    serde_json::to_value(entries).expect("Unable to serialize registry");
+                if let Some(object) = value.as_object_mut() {
+                    object.shift_insert(
+                        0,
+                        migrate::VERSION_KEY.to_string(),
+                        migrate::CURRENT_VERSION.into(),
+                    );
+                }
+                self.render(&value)
+            }
+        }
+    }
+
+    fn render_projects(self, projects: &Projects) -> String {
+        match self {
+            Format::Toml => projects.to_toml(),
+            _ => self.render(projects),
+        }
+    }
+
+    /// Renders YAML and JSON without the empty optional fields, like TOML does
+    fn render<T: Serialize>(self, value: &T) -> String {
+        let mut value = serde_json::to_value(value).expect("Unable to serialize data");
+        remove_nulls(&mut value);
+
+        match self {
+            Format::Toml => {
+                toml_edit::ser::to_string_pretty(&value).expect("Unable to serialize to TOML")
+            }
+            Format::Yaml => serde_yaml::to_string(&value).expect("Unable to serialize to YAML"),
+            Format::Json => {
+                let json =
+                    serde_json::to_string_pretty(&value).expect("Unable to serialize to JSON");
+                format!("{}\n", json)
+            }
+        }
+    }
+}
+
+/// Entry as it is written in a data file, before it's merged with the other layers
+pub struct RawEntry {
+    /// Slug of the project if the file gives it: the name of a per-project file, or the
+    /// `slug` key of an entry of a single file. Id of the entry for a registry.
+    pub id: Option<String>,
+    pub path: PathBuf,
+    /// Version the file was written with, the value is already upgraded to the current one
+    pub version: u32,
+    pub value: JsonValue,
+}
+
+/// Reads the entries of a category in a data directory without deserializing them, as the
+/// entries of an overlay only hold the fields they change. Entries are either in a file per
+/// project in the directory of the category (e.g. `clients/element.toml`), or in the legacy
+/// single file if it's still there, in TOML, YAML or JSON. Entries written with an older
+/// version of the schema are upgraded.
+pub fn read_raw<T: ProjectEntry>(data_dir: &str, errors: &mut Vec<String>) -> Vec<RawEntry> {
+    let mut entries = vec![];
+
+    for path in legacy_files(data_dir) {
+        let (mut legacy, version) = match read_versioned(&path) {
+            Ok(read) => read,
+            Err(e) => {
+                errors.push(format!("{}: {}", path.display(), e));
+                continue;
+            }
+        };
+
+        match legacy.get_mut(T::KEY).map(JsonValue::take) {
+            Some(JsonValue::Array(values)) => {
+                entries.extend(values.into_iter().map(|mut value| {
+                    migrate::upgrade(T::KEY, &mut value, version);
+                    RawEntry {
+                        id: value
+                            .get("slug")
+                            .and_then(JsonValue::as_str)
+                            .map(str::to_string),
+                        path: path.clone(),
+                        version,
+                        value,
+                    }
+                }))
+            }
+            Some(_) => errors.push(format!("{}: {} is not a list", path.display(), T::KEY)),
+            None => {}
+        }
+    }
+
+    for path in category_files::<T>(data_dir) {
+        let (mut value, version) = match read_versioned(&path) {
+            Ok(read) => read,
+            Err(e) => {
+                errors.push(format!("{}: {}", path.display(), e));
+                continue;
+            }
+        };
+        migrate::upgrade(T::KEY, &mut value, version);
+
+        entries.push(RawEntry {
+            id: path.file_stem().map(|s| s.to_string_lossy().into_owned()),
+            path,
+            version,
+            value,
+        });
+    }
+
+    entries
+}
+
+/// Reads a registry of a data directory without deserializing it, like `read_raw`
+pub fn read_raw_registry(
+    registry: Registry,
+    data_dir: &str,
+    errors: &mut Vec<String>,
+) -> Vec<RawEntry> {
+    let mut entries = vec![];
+
+    for path in legacy_files(data_dir) {
+        let (mut legacy, version) = match read_versioned(&path) {
+            Ok(read) => read,
+            Err(e) => {
+                errors.push(format!("{}: {}", path.display(), e));
+                continue;
+            }
+        };
+
+        match legacy.get_mut(registry.name).map(JsonValue::take) {
+            Some(JsonValue::Object(tables)) => {
+                entries.extend(raw_registry(registry, tables, &path, version));
+            }
+            Some(_) => errors.push(format!(
+                "{}: {} is not a table",
+                path.display(),
+                registry.name
+            )),
+            None => {}
+        }
+    }
+
+    for path in files_named(data_dir, registry.name) {
+        match read_versioned(&path) {
+            Ok((JsonValue::Object(tables), version)) => {
+                entries.extend(raw_registry(registry, tables, &path, version));
+            }
+            Ok(_) => errors.push(format!(
+                "{}: should hold a table per {}",
+                path.display(),
+                registry.kind
+            )),
+            Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    entries
+}
+
+/// Moves every entry of the legacy single files to its own file, in the same format
+pub fn split(data_dir: &str, output: &mut Output) {
+    let legacy_paths = legacy_files(data_dir);
+    if legacy_paths.is_empty() {
+        println!("No {}.* file to split in {}", LEGACY_FILE_STEM, data_dir);
+        return;
+    }
+
+    for legacy_path in legacy_paths {
+        let format = Format::from_path(&legacy_path).unwrap();
+        let contents = fs::read_to_string(&legacy_path)
+            .unwrap_or_else(|_| panic!("Unable to open {}", legacy_path.display()));
+        // The files of the entries have no comments, deleting this one would lose them
+        if warn_comments(&legacy_path, &contents, "split") {
+            continue;
+        }
+        let projects: Projects = parse_current(format, &contents)
+            .unwrap_or_else(|e| panic!("Unable to parse {}: {}", legacy_path.display(), e));
+
+        check_registry_collision(data_dir, AUTHORS, &projects.authors, &legacy_path);
+        check_registry_collision(data_dir, PROTOCOLS, &projects.protocols, &legacy_path);
+
+        split_category(data_dir, &projects.bots, format, output);
+        split_category(data_dir, &projects.bridges, format, output);
+        split_category(data_dir, &projects.clients, format, output);
+        split_category(data_dir, &projects.iots, format, output);
+        split_category(data_dir, &projects.others, format, output);
+        split_category(data_dir, &projects.sdks, format, output);
+        split_category(data_dir, &projects.servers, format, output);
+
+        split_registry(data_dir, AUTHORS, &projects.authors, format, output);
+        split_registry(data_dir, PROTOCOLS, &projects.protocols, format, output);
+
+        fs::remove_file(&legacy_path)
+            .unwrap_or_else(|_| panic!("Unable to delete {}", legacy_path.display()));
+        println!("Deleted {}", legacy_path.display());
+    }
+}
+
+/// Rewrites every data file in its canonical form, keeping its format, or only checks that
+/// they are if `check` is set. Returns whether they all were.
+pub fn format(data_dir: &str, check: bool, output: &mut Output) -> bool {
+    let mut formatted = true;
+
+    for path in legacy_files(data_dir) {
+        let format = Format::from_path(&path).unwrap();
+        let contents = fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("Unable to open {}", path.display()));
+        let mut projects: Projects = parse_current(format, &contents)
+            .unwrap_or_else(|e| panic!("Unable to parse {}: {}", path.display(), e));
+        projects.sort();
+        let canonical = format.render_projects(&projects);
+        formatted &= format_file(&path, &contents, canonical, check, output);
+    }
+
+    formatted &= format_registry::<AuthorDetails>(data_dir, AUTHORS, check, output);
+    formatted &= format_registry::<Protocol>(data_dir, PROTOCOLS, check, output);
+
+    formatted &= format_category::<Bot>(data_dir, check, output);
+    formatted &= format_category::<Bridge>(data_dir, check, output);
+    formatted &= format_category::<Client>(data_dir, check, output);
+    formatted &= format_category::<Iot>(data_dir, check, output);
+    formatted &= format_category::<Other>(data_dir, check, output);
+    formatted &= format_category::<Sdk>(data_dir, check, output);
+    formatted &= format_category::<Server>(data_dir, check, output);
+
+    formatted
+}
+
+/// Upgrades the data files of a directory to the current version of the schema, listing the
+/// changes applied to each of them. Returns the number of files upgraded.
+pub fn migrate(data_dir: &str, output: &mut Output) -> usize {
+    let mut migrated_files = 0;
+
+    for path in legacy_files(data_dir) {
+        let mut file: JsonValue =
+            read(&path).unwrap_or_else(|e| panic!("Unable to parse {}: {}", path.display(), e));
+        let version = migrate::version_of(&file)
+            .unwrap_or_else(|e| panic!("Unable to migrate {}: {}", path.display(), e));
+        if version == migrate::CURRENT_VERSION {
+            continue;
+        }
+
+        let mut applied = vec![];
+        if let Some(object) = file.as_object_mut() {
+            for (key, entries) in object.iter_mut() {
+                match entries {
+                    JsonValue::Array(entries) => {
+                        for entry in entries {
+                            applied.extend(migrate::upgrade(key, entry, version));
+                        }
+                    }
+                    JsonValue::Object(tables) if REGISTRIES.iter().any(|r| r.name == key) => {
+                        for entry in tables.values_mut() {
+                            applied.extend(migrate::upgrade(key, entry, version));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            object.shift_insert(
+                0,
+                migrate::VERSION_KEY.to_string(),
+                migrate::CURRENT_VERSION.into(),
+            );
+        }
+
+        let format = Format::from_path(&path).unwrap();
+        // Entries of overlays can't be rendered canonically as they only hold some fields
+        let migrated = match serde_json::from_value::<Projects>(file.clone()) {
+            Ok(projects) => format.render_projects(&projects),
+            Err(_) => format.render(&file),
+        };
+        if write_migrated(&path, version, applied, migrated, output) {
+            migrated_files += 1;
+        }
+    }
+
+    migrated_files += migrate_registry::<AuthorDetails>(data_dir, AUTHORS, output);
+    migrated_files += migrate_registry::<Protocol>(data_dir, PROTOCOLS, output);
+    migrated_files += migrate_category::<Bot>(data_dir, output);
+    migrated_files += migrate_category::<Bridge>(data_dir, output);
+    migrated_files += migrate_category::<Client>(data_dir, output);
+    migrated_files += migrate_category::<Iot>(data_dir, output);
+    migrated_files += migrate_category::<Other>(data_dir, output);
+    migrated_files += migrate_category::<Sdk>(data_dir, output);
+    migrated_files += migrate_category::<Server>(data_dir, output);
+    migrated_files
+}
+
+/// Adds a new entry to the legacy single TOML file if it's still used, or in its own TOML
+/// file otherwise. Returns the path of the file the entry was written to.
+pub fn add<T: ProjectEntry>(data_dir: &str, entry: &T) -> PathBuf {
+    let legacy_path = Path::new(data_dir).join(format!("{}.toml", LEGACY_FILE_STEM));
+    if legacy_path.exists() {
+        let mut data = fs::read_to_string(&legacy_path).expect("Unable to open master data file");
+        parse_current::<JsonValue>(Format::Toml, &data)
+            .unwrap_or_else(|e| panic!("Unable to parse {}: {}", legacy_path.display(), e));
+        if !data.is_empty() && !data.ends_with('\n') {
+            data.push('\n');
+        }
+        data.push('\n');
+        data.push_str(&templates::entry(entry));
+        fs::write(&legacy_path, data).expect("Unable to write master data file");
+        return legacy_path;
+    }
+
+    if let Some(existing) = existing_entry_file(data_dir, entry) {
+        panic!("{} already exists", existing.display());
+    }
+    let path = entry_path(data_dir, entry, Format::Toml);
+    fs::create_dir_all(path.parent().unwrap())
+        .unwrap_or_else(|_| panic!("Unable to create the directory of {}", path.display()));
+    fs::write(&path, Format::Toml.render_entry(entry))
+        .unwrap_or_else(|_| panic!("Unable to write {}", path.display()));
+    path
+}
+
+/// Whether the data directory has a legacy single file or a file in the directory of any
+/// category
+pub fn has_projects(data_dir: &str) -> bool {
+    !legacy_files(data_dir).is_empty()
+        || !category_files::<Bot>(data_dir).is_empty()
+        || !category_files::<Bridge>(data_dir).is_empty()
+        || !category_files::<Client>(data_dir).is_empty()
+        || !category_files::<Iot>(data_dir).is_empty()
+        || !category_files::<Other>(data_dir).is_empty()
+        || !category_files::<Sdk>(data_dir).is_empty()
+        || !category_files::<Server>(data_dir).is_empty()
+}
+
+/// JSON Schema of the file of a project, the entry along with the version of the schema
+pub fn entry_schema<T: ProjectEntry>() -> schemars::Schema {
+    let mut schema = schemars::schema_for!(T);
+    if let Some(properties) = schema
+        .get_mut("properties")
+        .and_then(JsonValue::as_object_mut)
+    {
+        properties.shift_insert(
+            0,
+            migrate::VERSION_KEY.to_string(),
+            serde_json::json!({
+                "description": "Version of the layout of the data, `squiddy migrate` upgrades older data",
+                "type": "integer",
+                "format": "uint32",
+                "minimum": 0,
+                "default": migrate::UNVERSIONED,
+            }),
+        );
+    }
+    schema
+}
+
+/// Reads a file in the format of its extension
+pub fn read<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let format = Format::from_path(path).ok_or_else(|| {
+        format!(
+            "unknown extension, the file should end with {}",
+            extensions().join(", ")
+        )
+    })?;
+    let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    format.parse(&contents)
+}
+
+fn split_category<T: ProjectEntry>(
+    data_dir: &str,
+    entries: &[T],
+    format: Format,
+    output: &mut Output,
+) {
+    for entry in entries {
+        if let Some(existing) = existing_entry_file(data_dir, entry) {
+            panic!(
+                "{} already exists, rename {} before splitting",
+                existing.display(),
+                entry.title()
+            );
+        }
+
+        let path = entry_path(data_dir, entry, format);
+        fs::create_dir_all(path.parent().unwrap())
+            .unwrap_or_else(|_| panic!("Unable to create the directory of {}", path.display()));
+        output.write("data", &path, format.render_entry(entry));
+    }
+}
+
+/// Refuses to split a registry of the legacy single file if it already has its own file
+fn check_registry_collision<T>(
+    data_dir: &str,
+    registry: Registry,
+    entries: &BTreeMap<String, T>,
+    legacy_path: &Path,
+) {
+    if let Some(existing) = files_named(data_dir, registry.name)
+        .first()
+        .filter(|_| !entries.is_empty())
+    {
+        panic!(
+            "{} already exists, move the {} of {} to it before splitting",
+            existing.display(),
+            registry.name,
+            legacy_path.display()
+        );
+    }
+}
+
+fn split_registry<T: Serialize>(
+    data_dir: &str,
+    registry: Registry,
+    entries: &BTreeMap<String, T>,
+    format: Format,
+    output: &mut Output,
+) {
+    if entries.is_empty() {
+        return;
+    }
+    let path = Path::new(data_dir).join(format!("{}.{}", registry.name, format.extension()));
+    output.write("data", path, format.render_registry(entries));
+}
+
+fn migrate_registry<T: Serialize + DeserializeOwned>(
+    data_dir: &str,
+    registry: Registry,
+    output: &mut Output,
+) -> usize {
+    let mut migrated_files = 0;
+
+    for path in files_named(data_dir, registry.name) {
+        let (mut tables, version) = read_versioned(&path)
+            .unwrap_or_else(|e| panic!("Unable to migrate {}: {}", path.display(), e));
+        if version == migrate::CURRENT_VERSION {
+            continue;
+        }
+
+        let mut applied = vec![];
+        for entry in tables
+            .as_object_mut()
+            .into_iter()
+            .flat_map(|t| t.values_mut())
+        {
+            applied.extend(migrate::upgrade(registry.name, entry, version));
+        }
+
+        let format = Format::from_path(&path).unwrap();
+        let migrated = match serde_json::from_value::<BTreeMap<String, T>>(tables.clone()) {
+            Ok(entries) => format.render_registry(&entries),
+            Err(_) => {
+                if let Some(object) = tables.as_object_mut() {
+                    object.shift_insert(
+                        0,
+                        migrate::VERSION_KEY.to_string(),
+                        migrate::CURRENT_VERSION.into(),
+                    );
+                }
+                format.render(&tables)
+            }
+        };
+        if write_migrated(&path, version, applied, migrated, output) {
+            migrated_files += 1;
+        }
+    }
+
+    migrated_files
+}
+
+fn migrate_category<T: ProjectEntry + DeserializeOwned>(
+    data_dir: &str,
+    output: &mut Output,
+) -> usize {
+    let mut migrated_files = 0;
+    for path in category_files::<T>(data_dir) {
+        let mut entry: JsonValue =
+            read(&path).unwrap_or_else(|e| panic!("Unable to parse {}: {}", path.display(), e));
+        let version = migrate::version_of(&entry)
+            .unwrap_or_else(|e| panic!("Unable to migrate {}: {}", path.display(), e));
+        if version == migrate::CURRENT_VERSION {
+            continue;
+        }
+
+        if let Some(object) = entry.as_object_mut() {
+            object.remove(migrate::VERSION_KEY);
+        }
+        let applied = migrate::upgrade(T::KEY, &mut entry, version);
+
+        let format = Format::from_path(&path).unwrap();
+        let migrated = match serde_json::from_value::<T>(entry.clone()) {
+            Ok(entry) => format.render_entry(&entry),
+            Err(_) => {
+                if let Some(object) = entry.as_object_mut() {
+                    object.shift_insert(
+                        0,
+                        migrate::VERSION_KEY.to_string(),
+                        migrate::CURRENT_VERSION.into(),
+                    );
+                }
+                format.render(&entry)
+            }
+        };
+        if write_migrated(&path, version, applied, migrated, output) {
+            migrated_files += 1;
+        }
+    }
+    migrated_files
+}
+
+/// Writes an upgraded data file, unless rewriting it would lose its comments. Returns whether
+/// it was written.
+fn write_migrated(
+    path: &Path,
+    version: u32,
+    applied: Vec<&str>,
+    migrated: String,
+    output: &mut Output,
+) -> bool {
+    let contents =
+        fs::read_to_string(path).unwrap_or_else(|_| panic!("Unable to open {}", path.display()));
+    if warn_comments(path, &contents, "migrate") {
+        return false;
+    }
+
+    println!(
+        "{}: {} {} -> {}",
+        path.display(),
+        migrate::VERSION_KEY,
+        version,
+        migrate::CURRENT_VERSION
+    );
+    for change in applied.into_iter().unique() {
+        println!("  - {}", change);
+    }
+    output.write("data", path, migrated);
+    true
+}
+
+/// Parses a data file about to be rewritten, which has to be at the current version of the schema
+fn parse_current<T: DeserializeOwned>(format: Format, contents: &str) -> Result<T, String> {
+    let version = migrate::version_of(&format.parse::<JsonValue>(contents)?)?;
+    if version != migrate::CURRENT_VERSION {
+        return Err(format!(
+            "{} {} is outdated, run `squiddy migrate` first",
+            migrate::VERSION_KEY,
+            version
+        ));
+    }
+    format.parse(contents)
+}
+
+fn format_registry<T: Serialize + DeserializeOwned>(
+    data_dir: &str,
+    registry: Registry,
+    check: bool,
+    output: &mut Output,
+) -> bool {
+    let mut formatted = true;
+
+    for path in files_named(data_dir, registry.name) {
+        let format = Format::from_path(&path).unwrap();
+        let contents = fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("Unable to open {}", path.display()));
+        let entries = parse_current::<JsonValue>(format, &contents)
+            .and_then(|mut tables| {
+                if let Some(object) = tables.as_object_mut() {
+                    object.remove(migrate::VERSION_KEY);
+                }
+                serde_json::from_value::<BTreeMap<String, T>>(tables).map_err(|e| e.to_string())
+            })
+            .unwrap_or_else(|e| panic!("Unable to parse {}: {}", path.display(), e));
+        let canonical = format.render_registry(&entries);
+        formatted &= format_file(&path, &contents, canonical, check, output);
+    }
+
+    formatted
+}
+
+fn format_category<T: ProjectEntry + DeserializeOwned>(
+    data_dir: &str,
+    check: bool,
+    output: &mut Output,
+) -> bool {
+    let mut formatted = true;
+    for path in category_files::<T>(data_dir) {
+        let format = Format::from_path(&path).unwrap();
+        let contents = fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("Unable to open {}", path.display()));
+        let entry: T = parse_current(format, &contents)
+            .unwrap_or_else(|e| panic!("Unable to parse {}: {}", path.display(), e));
+        formatted &= format_file(&path, &contents, format.render_entry(&entry), check, output);
+    }
+    formatted
+}
+
+fn format_file(
+    path: &Path,
+    contents: &str,
+    formatted: String,
+    check: bool,
+    output: &mut Output,
+) -> bool {
+    // The canonical form has no comments, rewriting the file would lose them
+    if formatted != contents && warn_comments(path, contents, "format") {
+        return false;
+    }
+
+    if check {
+        if formatted != contents {
+            println!("{} is not formatted", path.display());
+        }
+    } else {
+        output.write("data", path, &formatted);
+    }
+    formatted == contents
+}
+
+/// Warns that a file with comments is left as it is, as the action would lose them. Returns
+/// whether it has any.
+fn warn_comments(path: &Path, contents: &str, action: &str) -> bool {
+    let comments = Format::from_path(path)
+        .map(|format| commented_lines(format, contents))
+        .unwrap_or_default();
+    if let Some(first) = comments.first() {
+        println!(
+            "Warning: {} has comments, the first one on line {}, remove them to {} it",
+            path.display(),
+            first,
+            action
+        );
+    }
+    !comments.is_empty()
+}
+
+/// Lines of a file holding a comment, found by checking that the file still has the same
+/// value without what follows a `#` on the line
+fn commented_lines(format: Format, contents: &str) -> Vec<usize> {
+    let value = match format {
+        Format::Json => return vec![],
+        _ => format.parse::<JsonValue>(contents),
+    };
+    let lines: Vec<&str> = contents.lines().collect();
+
+    (0..lines.len())
+        .filter(|&index| {
+            let line = lines[index];
+            let start = line
+                .char_indices()
+                .find(|&(i, c)| c == '#' && (i == 0 || line[..i].ends_with(char::is_whitespace)));
+            let Some((start, _)) = start else {
+                return false;
+            };
+            let mut without = lines.clone();
+            without[index] = &line[..start];
+            // Keep the final line break, it is part of a trailing YAML block
+            let mut without = without.join("\n");
+            without.push('\n');
+            format.parse::<JsonValue>(&without) == value
+        })
+        .map(|index| index + 1)
+        .collect()
+}
+
+/// Legacy single files present in the data directory, whatever their format
+fn legacy_files(data_dir: &str) -> Vec<PathBuf> {
+    files_named(data_dir, LEGACY_FILE_STEM)
+}
+
+/// Files of the data directory with the given name, whatever their format
+fn files_named(data_dir: &str, stem: &str) -> Vec<PathBuf> {
+    Format::ALL
+        .iter()
+        .map(|f| Path::new(data_dir).join(format!("{}.{}", stem, f.extension())))
+        .filter(|p| p.exists())
+        .collect()
+}
+
+/// Reads a data file, returning it without its version key along with the version
+fn read_versioned(path: &Path) -> Result<(JsonValue, u32), String> {
+    let mut value = read::<JsonValue>(path)?;
+    let version = migrate::version_of(&value)?;
+    if let Some(object) = value.as_object_mut() {
+        object.remove(migrate::VERSION_KEY);
+    }
+    Ok((value, version))
+}
+
+fn raw_registry(
+    registry: Registry,
+    tables: Map<String, JsonValue>,
+    path: &Path,
+    version: u32,
+) -> impl Iterator<Item = RawEntry> + '_ {
+    tables.into_iter().map(move |(id, mut value)| {
+        migrate::upgrade(registry.name, &mut value, version);
+        RawEntry {
+            id: Some(id),
+            path: path.to_path_buf(),
+            version,
+            value,
+        }
+    })
+}
+
+/// Data files of the category directory, in a stable order
+fn category_files<T: ProjectEntry>(data_dir: &str) -> Vec<PathBuf> {
+    let directory = Path::new(data_dir).join(T::KEY);
+    let mut files: Vec<PathBuf> = match fs::read_dir(&directory) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| Format::from_path(p).is_some())
+            .collect(),
+        Err(_) => vec![],
+    };
+    files.sort();
+    files
+}
+
+fn entry_path<T: ProjectEntry>(data_dir: &str, entry: &T, format: Format) -> PathBuf {
+    Path::new(data_dir)
+        .join(T::KEY)
+        .join(format!("{}.{}", entry.slug(), format.extension()))
+}
+
+/// File of the entry in any format, two files with the same slug would describe the same project
+fn existing_entry_file<T: ProjectEntry>(data_dir: &str, entry: &T) -> Option<PathBuf> {
+    Format::ALL
+        .iter()
+        .map(|f| entry_path(data_dir, entry, *f))
+        .find(|p| p.exists())
+}
+
+fn extensions() -> Vec<String> {
+    Format::ALL
+        .iter()
+        .map(|f| format!(".{}", f.extension()))
+        .collect()
+}
+
+fn remove_nulls(value: &mut JsonValue) {
+    match value {
+        JsonValue::Object(object) => {
+            object.retain(|_, v| !v.is_null());
+            object.values_mut().for_each(remove_nulls);
+        }
+        JsonValue::Array(array) => array.iter_mut().for_each(remove_nulls),
+        _ => {}
+    }
+}
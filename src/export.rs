@@ -0,0 +1,75 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::projects::{ProjectEntry, Projects};
+
+/// Version of the export format, to bump whenever a field is removed or changes meaning.
+/// See `doc/json-export.md` for the description of the format.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Machine-readable document listing every project of the data file
+#[derive(Serialize, Debug)]
+pub struct Export {
+    pub version: u32,
+    pub projects: Vec<ExportedProject>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ExportedProject {
+    pub slug: String,
+    pub category: &'static str,
+    pub matrixdotorg_url: String,
+    /// All the fields of the entry as they are in the data file
+    #[serde(flatten)]
+    pub data: Value,
+}
+
+impl Export {
+    pub fn new(projects: &Projects) -> Self {
+        let mut export = Export {
+            version: FORMAT_VERSION,
+            projects: vec![],
+        };
+
+        export.push_all(&projects.bots);
+        export.push_all(&projects.bridges);
+        export.push_all(&projects.clients);
+        export.push_all(&projects.iots);
+        export.push_all(&projects.others);
+        export.push_all(&projects.sdks);
+        export.push_all(&projects.servers);
+
+        export
+    }
+
+    /// Renders the export, with keys and projects always in the same order so that it
+    /// can be diffed across runs
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("Unable to serialize export");
+        json.push('\n');
+        json
+    }
+
+    fn push_all<T: ProjectEntry>(&mut self, entries: &[T]) {
+        let mut exported: Vec<ExportedProject> = entries
+            .iter()
+            .map(|entry| {
+                let mut data = serde_json::to_value(entry).expect("Unable to serialize project");
+                // The computed slug takes precedence over the optional one of the data file
+                if let Value::Object(fields) = &mut data {
+                    fields.remove("slug");
+                }
+
+                ExportedProject {
+                    slug: entry.slug(),
+                    category: T::CATEGORY,
+                    matrixdotorg_url: entry.matrixdotorg_url(),
+                    data,
+                }
+            })
+            .collect();
+        exported.sort_by(|a, b| a.slug.cmp(&b.slug));
+
+        self.projects.extend(exported);
+    }
+}
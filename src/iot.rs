@@ -1,30 +1,146 @@
 use convert_case::{Case, Casing};
-use indoc::formatdoc;
+use indoc::{formatdoc, indoc};
 use itertools::Itertools;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::projects::Author;
+use crate::projects::{self, Author, Maturity, ProjectEntry, ProjectLink};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// Memory and storage a project needs
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct Footprint {
+    /// Kilobytes of memory
+    pub ram_kb: Option<u32>,
+    /// Kilobytes of storage
+    pub storage_kb: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct Iot {
     pub title: String,
+    /// One-line summary of the project
     pub description: String,
     pub authors: Vec<Author>,
+    /// Can be Obsolete, Alpha, Beta or Stable
+    #[schemars(with = "Maturity")]
     pub maturity: String,
     pub language: String,
+    /// Has to be one of https://spdx.org/licenses/
     pub license: String,
     pub repository: Option<String>,
     pub home: Option<String>,
     pub screenshot: Option<String>,
     pub icon: Option<String>,
+    /// Alias of the room to discuss the project
     pub room: Option<String>,
+    /// SDKs the project is built on, slugs or titles of SDK entries
+    #[serde(default)]
+    pub sdk: Vec<String>,
+    /// Pages of the SDKs of `sdk` that are in the data
+    #[serde(skip)]
+    pub sdk_links: Vec<ProjectLink>,
     pub featured: bool,
     pub sort_order: Option<i32>,
+    /// Emoji the TWIM bot uses for the project
+    pub twim_emoji: Option<String>,
+    /// Hardware and platforms the project runs on or works with, e.g. "ESP32" or "Home Assistant"
+    #[serde(default)]
+    pub hardware: Vec<String>,
+    /// Protocols the project talks to devices with, e.g. "MQTT" or "Zigbee"
+    #[serde(default)]
+    pub transports: Vec<String>,
+    /// Resources the project needs on the device it runs on
+    pub footprint: Option<Footprint>,
+    /// Multi-line description, supports markdown
+    #[schemars(extend("x-multiline" = true))]
     pub full_description: String,
 }
 
-impl Iot {
-    pub fn to_markdown(&self) -> String {
+impl ProjectEntry for Iot {
+    const CATEGORY: &'static str = "iot";
+    const KEY: &'static str = "iots";
+    const DIRECTORY: &'static str = "iot";
+
+    fn example() -> Self {
+        Iot {
+            title: "Matrix Goes Vroom".to_string(),
+            description:
+                "MGV relies on Matrix to report health checks to the manufacturer of smart cars"
+                    .to_string(),
+            authors: vec![
+                Author {
+                    name: "Fred".to_string(),
+                    matrix_id: Some("@fred:server.tld".to_string()),
+                    ..Default::default()
+                },
+                Author {
+                    name: "MGV Corp.".to_string(),
+                    matrix_id: None,
+                    ..Default::default()
+                },
+            ],
+            maturity: "Stable".to_string(),
+            language: "Rust".to_string(),
+            license: "AGPL-3.0-or-later".to_string(),
+            repository: Some("https://git.matrixgoesvroom.tld/mgv".to_string()),
+            home: Some("https://matrixgoesvroom.tld".to_string()),
+            screenshot: Some("/data/matrixgoesvroom-screenshot.png".to_string()),
+            icon: Some("/data/matrixgoesvroom-icon.svg".to_string()),
+            room: Some("#matrixgoesvroom:server.tld".to_string()),
+            sdk: vec!["matrix-rust-sdk".to_string()],
+            sdk_links: vec![],
+            featured: false,
+            sort_order: Some(1),
+            twim_emoji: Some("🚗".to_string()),
+            hardware: vec!["Raspberry Pi".to_string(), "ESP32".to_string()],
+            transports: vec!["MQTT".to_string(), "CAN bus".to_string()],
+            footprint: Some(Footprint {
+                ram_kb: Some(512),
+                storage_kb: Some(2048),
+            }),
+            full_description: indoc! {"
+                A multi-line description that supports markdown to describe the project extensively.
+
+                Matrix Goes Vroom gets in electric vehicles, which don't really make Vroom but
+                the name sounded funny.
+            "}
+            .to_string(),
+        }
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn authors(&self) -> &[Author] {
+        &self.authors
+    }
+
+    fn authors_mut(&mut self) -> &mut Vec<Author> {
+        &mut self.authors
+    }
+
+    fn license(&self) -> &str {
+        &self.license
+    }
+
+    fn room(&self) -> Option<&str> {
+        self.room.as_deref()
+    }
+
+    fn sdk(&self) -> &[String] {
+        &self.sdk
+    }
+
+    fn set_sdk_links(&mut self, links: Vec<ProjectLink>) {
+        self.sdk_links = links;
+    }
+
+    fn sort_order(&self) -> Option<i32> {
+        self.sort_order
+    }
+
+    fn to_markdown(&self) -> String {
         let layout = match self.icon {
             Some(_) => "projectimage",
             None => "project",
@@ -48,6 +164,11 @@ impl Iot {
             self.icon.as_ref().map(|i| format!("thumbnail: {i}")),
             self.room.as_ref().map(|r| format!("room: \"{r}\"")),
             self.sort_order.as_ref().map(|o| format!("sort_order: {o}")),
+            projects::front_matter_list("sdk", &self.sdk),
+            ProjectLink::front_matter("sdk_links", &self.sdk_links),
+            projects::front_matter_list("hardware", &self.hardware),
+            projects::front_matter_list("transports", &self.transports),
+            self.footprint.as_ref().and_then(Footprint::front_matter),
         ]
         .iter()
         .flatten()
@@ -73,7 +194,106 @@ impl Iot {
         "}
     }
 
-    pub fn filename(&self) -> String {
+    fn filename(&self) -> String {
         format!("{}.mdx", self.title.to_case(Case::Kebab))
     }
 }
+
+impl Footprint {
+    /// Front matter of the known resources, or nothing if none is
+    fn front_matter(&self) -> Option<String> {
+        let resources = [
+            self.ram_kb.map(|r| format!("    ram_kb: {r}")),
+            self.storage_kb.map(|s| format!("    storage_kb: {s}")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        if resources.is_empty() {
+            None
+        } else {
+            Some(format!("footprint:\n{}", resources.join("\n")))
+        }
+    }
+}
+
+impl Iot {
+    /// matrix.org page listing the projects by hardware and by transport so that they can be
+    /// filtered, with a table of what each project runs on. Nothing if no project gives its
+    /// hardware or transports.
+    pub fn index_page(iots: &[Iot]) -> Option<String> {
+        let iots = iots
+            .iter()
+            .filter(|i| !i.hardware.is_empty() || !i.transports.is_empty())
+            .sorted_by(|a, b| a.title.cmp(&b.title))
+            .collect::<Vec<_>>();
+        if iots.is_empty() {
+            return None;
+        }
+
+        let hardware = group_front_matter("hardware", &iots, |i| &i.hardware);
+        let transports = group_front_matter("transports", &iots, |i| &i.transports);
+        let rows = iots
+            .iter()
+            .map(|iot| {
+                let footprint = iot.footprint.as_ref();
+                let kb =
+                    |value: Option<u32>| value.map_or("unknown".to_string(), |v| v.to_string());
+                format!(
+                    "| [{}]({}) | {} | {} | {} | {} |",
+                    iot.title,
+                    iot.matrixdotorg_url(),
+                    iot.hardware.join(", "),
+                    iot.transports.join(", "),
+                    kb(footprint.and_then(|f| f.ram_kb)),
+                    kb(footprint.and_then(|f| f.storage_kb)),
+                )
+            })
+            .join("\n");
+
+        Some(formatdoc! {"
+            ---
+            layout: iotindex
+            title: IoT projects
+            categories:
+             - iot
+            {hardware}
+            {transports}
+            ---
+            | Project | Hardware | Transports | RAM (KB) | Storage (KB) |
+            | --- | --- | --- | --- | --- |
+            {rows}
+        "})
+    }
+}
+
+/// Front matter list of the values of a field, each with the projects that have it
+fn group_front_matter(key: &str, iots: &[&Iot], values: impl Fn(&Iot) -> &[String]) -> String {
+    let groups = iots
+        .iter()
+        .flat_map(|iot| values(iot).iter().map(move |value| (value, *iot)))
+        .into_group_map();
+    let items = groups
+        .into_iter()
+        .sorted_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(value, iots)| {
+            let projects = iots
+                .iter()
+                .map(|iot| {
+                    format!(
+                        "        - title: {}\n          url: {}",
+                        iot.title,
+                        iot.matrixdotorg_url()
+                    )
+                })
+                .join("\n");
+            format!("    - name: {}\n      projects:\n{}", value, projects)
+        })
+        .join("\n");
+
+    if items.is_empty() {
+        format!("{}: []", key)
+    } else {
+        format!("{}:\n{}", key, items)
+    }
+}
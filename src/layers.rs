@@ -0,0 +1,691 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    process,
+};
+
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value as JsonValue};
+
+use crate::{
+    data::{self, RawEntry, Registry},
+    migrate,
+    projects::{Author, AuthorDetails, ProjectEntry, Projects},
+    protocol, sdk_usage, validate,
+};
+
+/// Key an overlay sets to leave a project out, e.g. `hidden = true`
+const HIDDEN_KEY: &str = "hidden";
+
+/// Where the fields of a project come from once the layers are merged
+#[derive(Clone, Debug)]
+pub struct Provenance {
+    pub category: &'static str,
+    pub id: String,
+    /// Layers other than the data directory that define or change the project
+    pub overlays: Vec<String>,
+    /// Layer that hid the project, if any
+    pub hidden_by: Option<String>,
+    /// Dotted path of each field, with the layer that set it
+    pub fields: Vec<(String, String)>,
+}
+
+impl Provenance {
+    pub fn is_overridden(&self) -> bool {
+        !self.overlays.is_empty()
+    }
+}
+
+/// What the loading of each category shares
+struct Loader<'a> {
+    layers: &'a [String],
+    authors: BTreeMap<String, AuthorDetails>,
+    provenance: Vec<Provenance>,
+    outdated: Vec<PathBuf>,
+    errors: Vec<String>,
+}
+
+impl Loader<'_> {
+    fn category<T: ProjectEntry + DeserializeOwned>(&mut self) -> Vec<T> {
+        load_category(
+            self.layers,
+            &self.authors,
+            &mut self.provenance,
+            &mut self.outdated,
+            &mut self.errors,
+        )
+    }
+}
+
+/// Entry being merged, field by field, from every layer that mentions it
+struct Merged {
+    id: String,
+    value: Map<String, JsonValue>,
+    paths: Vec<PathBuf>,
+    overlays: Vec<String>,
+    fields: HashMap<String, String>,
+    hidden_by: Option<String>,
+}
+
+/// Loads the data directories on top of each other: entries are matched by slug and the
+/// fields of later layers override the ones of earlier layers, so that an overlay only
+/// holds what it changes. Authors referenced by id are resolved against the author registry,
+/// the SDKs of the projects against the SDK entries and the networks of the bridges against
+/// the protocol registry.
+/// Exits listing every file that couldn't be loaded, or if the base data directory has no
+/// projects at all, e.g. when squiddy is run from the wrong directory.
+pub fn load(layers: &[String]) -> (Projects, Vec<Provenance>) {
+    if let Some(base) = layers.first() {
+        if !data::has_projects(base) {
+            eprintln!(
+                "Unable to load the data: {} has no {} file and no project files",
+                base,
+                data::LEGACY_FILE_STEM
+            );
+            process::exit(1);
+        }
+    }
+
+    let mut loader = Loader {
+        layers,
+        authors: BTreeMap::new(),
+        provenance: vec![],
+        outdated: vec![],
+        errors: vec![],
+    };
+
+    loader.authors = load_registry(
+        layers,
+        data::AUTHORS,
+        check_author,
+        &mut loader.outdated,
+        &mut loader.errors,
+    );
+    let protocols = load_registry(
+        layers,
+        data::PROTOCOLS,
+        |_| vec![],
+        &mut loader.outdated,
+        &mut loader.errors,
+    );
+    let mut projects = Projects {
+        schema_version: migrate::CURRENT_VERSION,
+        bots: loader.category(),
+        bridges: loader.category(),
+        clients: loader.category(),
+        iots: loader.category(),
+        others: loader.category(),
+        sdks: loader.category(),
+        servers: loader.category(),
+        authors: loader.authors.clone(),
+        protocols,
+    };
+    for warning in sdk_usage::resolve(&mut projects) {
+        println!("Warning: {}", warning);
+    }
+    for warning in protocol::resolve(&mut projects) {
+        println!("Warning: {}", warning);
+    }
+    let Loader {
+        provenance,
+        outdated,
+        errors,
+        ..
+    } = loader;
+
+    let outdated = outdated.into_iter().unique().count();
+    if outdated > 0 {
+        println!(
+            "Warning: {} data files use an older {}, run `squiddy migrate` to upgrade them",
+            outdated,
+            migrate::VERSION_KEY
+        );
+    }
+
+    if !errors.is_empty() {
+        eprintln!("Unable to load the data:");
+        // Legacy single files are read once per category
+        for error in errors.into_iter().unique() {
+            eprintln!("  - {}", error);
+        }
+        process::exit(1);
+    }
+
+    (projects, provenance)
+}
+
+/// Prints the layer each field of the projects comes from, only for the projects changed by
+/// an overlay unless `all` is set
+pub fn print_report(provenance: &[Provenance], all: bool) {
+    let mut overridden = 0;
+    for project in provenance {
+        if !project.is_overridden() {
+            if all {
+                print_project(project);
+            }
+            continue;
+        }
+
+        overridden += 1;
+        print_project(project);
+    }
+
+    println!("{} projects changed by an overlay", overridden);
+}
+
+fn print_project(project: &Provenance) {
+    println!("{}/{}", project.category, project.id);
+    if let Some(layer) = &project.hidden_by {
+        println!("  hidden by {}", layer);
+    }
+    for (field, layer) in &project.fields {
+        println!("  {}: {}", field, layer);
+    }
+}
+
+fn load_category<T: ProjectEntry + DeserializeOwned>(
+    layers: &[String],
+    authors: &BTreeMap<String, AuthorDetails>,
+    provenance: &mut Vec<Provenance>,
+    outdated: &mut Vec<PathBuf>,
+    errors: &mut Vec<String>,
+) -> Vec<T> {
+    let mut fields = field_names::<T>();
+    // Entries of a single file can give their slug
+    fields.push("slug".to_string());
+    let merged = merge_layers(
+        layers,
+        T::CATEGORY,
+        &fields,
+        data::read_raw::<T>,
+        entry_id::<T>,
+        outdated,
+        errors,
+    );
+
+    let mut entries = vec![];
+    for entry in merged {
+        let files = entry.paths.iter().map(|p| p.display()).join(" + ");
+        let value = JsonValue::Object(entry.value);
+        let mut fields = vec![];
+        leaf_paths(&value, "", &mut fields);
+        provenance.push(Provenance {
+            category: T::KEY,
+            id: entry.id,
+            overlays: entry.overlays,
+            hidden_by: entry.hidden_by.clone(),
+            fields: fields
+                .into_iter()
+                .map(|f| {
+                    let layer = entry.fields.get(&f).cloned().unwrap_or_default();
+                    (f, layer)
+                })
+                .collect(),
+        });
+
+        if entry.hidden_by.is_some() {
+            continue;
+        }
+
+        match serde_json::from_value::<T>(value) {
+            Ok(mut parsed) => {
+                for problem in parsed.validate() {
+                    println!("Warning: {}: {}", files, problem);
+                }
+                for id in resolve_authors(&mut parsed, authors) {
+                    errors.push(format!("{}: unknown author {}", files, id));
+                }
+                entries.push(parsed);
+            }
+            Err(e) => errors.push(format!("{}: {}", files, e)),
+        }
+    }
+    entries
+}
+
+/// Loads a registry of every layer, merged like the projects. `check` returns the problems
+/// of an entry that can't be expressed in its type.
+fn load_registry<T: DeserializeOwned + JsonSchema>(
+    layers: &[String],
+    registry: Registry,
+    check: impl Fn(&T) -> Vec<String>,
+    outdated: &mut Vec<PathBuf>,
+    errors: &mut Vec<String>,
+) -> BTreeMap<String, T> {
+    let merged = merge_layers(
+        layers,
+        registry.kind,
+        &field_names::<T>(),
+        |layer, errors| data::read_raw_registry(registry, layer, errors),
+        |raw, _| raw.id.clone(),
+        outdated,
+        errors,
+    );
+
+    let mut entries = BTreeMap::new();
+    for entry in merged {
+        let files = entry.paths.iter().map(|p| p.display()).join(" + ");
+        match serde_json::from_value::<T>(JsonValue::Object(entry.value)) {
+            Ok(parsed) => {
+                for problem in check(&parsed) {
+                    println!("Warning: {}: {}", files, problem);
+                }
+                entries.insert(entry.id, parsed);
+            }
+            Err(e) => errors.push(format!("{}: {} {}: {}", files, registry.kind, entry.id, e)),
+        }
+    }
+    entries
+}
+
+fn check_author(author: &AuthorDetails) -> Vec<String> {
+    author
+        .matrix_id
+        .as_deref()
+        .and_then(|id| validate::matrix_id(id).err())
+        .into_iter()
+        .collect()
+}
+
+/// Replaces the references to the registry by the authors they point to, returns the ids
+/// that don't exist
+fn resolve_authors<T: ProjectEntry>(
+    entry: &mut T,
+    authors: &BTreeMap<String, AuthorDetails>,
+) -> Vec<String> {
+    let mut unknown = vec![];
+    for author in entry.authors_mut() {
+        if !author.is_unresolved() {
+            continue;
+        }
+        let id = author.id.clone().unwrap_or_default();
+        match authors.get(&id) {
+            Some(details) => *author = Author::from_details(Some(id), details.clone()),
+            None => unknown.push(id),
+        }
+    }
+    unknown
+}
+
+/// Top-level fields of an entry, as named in the data files
+fn field_names<T: JsonSchema>() -> Vec<String> {
+    schemars::schema_for!(T)
+        .get("properties")
+        .and_then(JsonValue::as_object)
+        .map(|properties| properties.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Merges the entries of every layer, matching them by id. The entries of overlays can only
+/// set `fields`, so that a misspelt field isn't silently ignored.
+fn merge_layers(
+    layers: &[String],
+    kind: &str,
+    fields: &[String],
+    read: impl Fn(&str, &mut Vec<String>) -> Vec<RawEntry>,
+    id_of: impl Fn(&RawEntry, &mut Vec<String>) -> Option<String>,
+    outdated: &mut Vec<PathBuf>,
+    errors: &mut Vec<String>,
+) -> Vec<Merged> {
+    let mut merged: Vec<Merged> = vec![];
+
+    for (layer_index, layer) in layers.iter().enumerate() {
+        let mut seen = vec![];
+        for raw in read(layer, errors) {
+            if raw.version < migrate::CURRENT_VERSION {
+                outdated.push(raw.path.clone());
+            }
+            let Some(id) = id_of(&raw, errors) else {
+                continue;
+            };
+            if seen.contains(&id) {
+                errors.push(format!(
+                    "{}: {} {} is already defined in {}",
+                    raw.path.display(),
+                    kind,
+                    id,
+                    layer
+                ));
+                continue;
+            }
+            seen.push(id.clone());
+
+            let JsonValue::Object(mut patch) = raw.value else {
+                errors.push(format!("{}: {} is not a table", raw.path.display(), kind));
+                continue;
+            };
+
+            let index = match merged.iter().position(|m| m.id == id) {
+                Some(index) => index,
+                None => {
+                    merged.push(Merged {
+                        id,
+                        value: Map::new(),
+                        paths: vec![],
+                        overlays: vec![],
+                        fields: HashMap::new(),
+                        hidden_by: None,
+                    });
+                    merged.len() - 1
+                }
+            };
+            let entry = &mut merged[index];
+
+            match patch.remove(HIDDEN_KEY) {
+                Some(JsonValue::Bool(true)) => entry.hidden_by = Some(layer.clone()),
+                Some(JsonValue::Bool(false)) => entry.hidden_by = None,
+                Some(_) => errors.push(format!(
+                    "{}: {} should be true or false",
+                    raw.path.display(),
+                    HIDDEN_KEY
+                )),
+                None => {}
+            }
+            if layer_index > 0 {
+                for key in patch.keys().filter(|key| !fields.contains(key)) {
+                    errors.push(format!(
+                        "{}: {} has no field {}",
+                        raw.path.display(),
+                        kind,
+                        key
+                    ));
+                }
+            }
+            merge(&mut entry.value, patch, "", layer, &mut entry.fields);
+            entry.paths.push(raw.path);
+            if layer_index > 0 && !entry.overlays.contains(layer) {
+                entry.overlays.push(layer.clone());
+            }
+        }
+    }
+
+    merged
+}
+
+/// Slug the entry is matched with across layers
+fn entry_id<T: ProjectEntry + DeserializeOwned>(
+    raw: &RawEntry,
+    errors: &mut Vec<String>,
+) -> Option<String> {
+    if let Some(id) = &raw.id {
+        return Some(id.clone());
+    }
+
+    // Entries of a single file without a `slug` key are identified by the slug of their
+    // title, which needs the whole entry
+    match serde_json::from_value::<T>(raw.value.clone()) {
+        Ok(entry) => Some(entry.slug()),
+        Err(e) => {
+            errors.push(format!(
+                "{}: {}, partial entries need a slug key",
+                raw.path.display(),
+                e
+            ));
+            None
+        }
+    }
+}
+
+/// Sets the fields of `patch` in `value`, going down into nested tables so that e.g.
+/// `features.e2ee` can be overridden alone. Lists are replaced as a whole.
+fn merge(
+    value: &mut Map<String, JsonValue>,
+    patch: Map<String, JsonValue>,
+    prefix: &str,
+    layer: &str,
+    fields: &mut HashMap<String, String>,
+) {
+    for (key, patch_value) in patch {
+        let path = match prefix {
+            "" => key.clone(),
+            _ => format!("{}.{}", prefix, key),
+        };
+
+        match (value.get_mut(&key), patch_value) {
+            (Some(JsonValue::Object(nested)), JsonValue::Object(nested_patch)) => {
+                merge(nested, nested_patch, &path, layer, fields);
+            }
+            (_, patch_value) => {
+                let nested_prefix = format!("{}.", path);
+                fields.retain(|f, _| *f != path && !f.starts_with(&nested_prefix));
+                let mut leaves = vec![];
+                leaf_paths(&patch_value, &path, &mut leaves);
+                fields.extend(leaves.into_iter().map(|f| (f, layer.to_string())));
+                value.insert(key, patch_value);
+            }
+        }
+    }
+}
+
+/// Dotted paths of the fields that aren't tables, lists count as a single field
+fn leaf_paths(value: &JsonValue, prefix: &str, paths: &mut Vec<String>) {
+    match value {
+        JsonValue::Object(object) => {
+            for (key, nested) in object {
+                let path = match prefix {
+                    "" => key.clone(),
+                    _ => format!("{}.{}", prefix, key),
+                };
+                leaf_paths(nested, &path, paths);
+            }
+        }
+        _ => paths.push(prefix.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// Merges layers given as their name and the entries they define, by id
+    fn merge_entries(layers: &[(&str, Vec<(&str, JsonValue)>)]) -> (Vec<Merged>, Vec<String>) {
+        let names: Vec<String> = layers.iter().map(|(name, _)| name.to_string()).collect();
+        let read = |layer: &str, _: &mut Vec<String>| {
+            let (_, entries) = layers.iter().find(|(name, _)| *name == layer).unwrap();
+            entries
+                .iter()
+                .map(|(id, value)| RawEntry {
+                    id: Some(id.to_string()),
+                    path: PathBuf::from(format!("{}/{}.toml", layer, id)),
+                    version: migrate::CURRENT_VERSION,
+                    value: value.clone(),
+                })
+                .collect()
+        };
+
+        let mut errors = vec![];
+        let fields = ["title", "home", "authors", "features"].map(str::to_string);
+        let merged = merge_layers(
+            &names,
+            "client",
+            &fields,
+            read,
+            |raw, _| raw.id.clone(),
+            &mut vec![],
+            &mut errors,
+        );
+        (merged, errors)
+    }
+
+    #[test]
+    fn merges_nested_tables_field_by_field() {
+        let (merged, errors) = merge_entries(&[
+            (
+                "data",
+                vec![(
+                    "element",
+                    json!({"title": "Element", "features": {"e2ee": "yes", "spaces": "no"}}),
+                )],
+            ),
+            (
+                "private",
+                vec![("element", json!({"features": {"spaces": "partial"}}))],
+            ),
+        ]);
+
+        assert!(errors.is_empty());
+        assert_eq!(merged.len(), 1);
+        let element = &merged[0];
+        assert_eq!(
+            JsonValue::Object(element.value.clone()),
+            json!({"title": "Element", "features": {"e2ee": "yes", "spaces": "partial"}})
+        );
+        assert_eq!(element.overlays, vec!["private"]);
+        assert_eq!(element.fields["title"], "data");
+        assert_eq!(element.fields["features.e2ee"], "data");
+        assert_eq!(element.fields["features.spaces"], "private");
+    }
+
+    #[test]
+    fn replaces_lists_as_a_whole() {
+        let (merged, _) = merge_entries(&[
+            (
+                "data",
+                vec![(
+                    "element",
+                    json!({"authors": [{"name": "Fred"}, {"name": "Alice"}]}),
+                )],
+            ),
+            (
+                "private",
+                vec![("element", json!({"authors": [{"name": "Bob"}]}))],
+            ),
+        ]);
+
+        assert_eq!(merged[0].value["authors"], json!([{"name": "Bob"}]));
+        assert_eq!(merged[0].fields["authors"], "private");
+    }
+
+    #[test]
+    fn replacing_a_table_forgets_the_layers_of_its_fields() {
+        let (merged, _) = merge_entries(&[
+            (
+                "data",
+                vec![("element", json!({"features": {"e2ee": "yes"}}))],
+            ),
+            ("private", vec![("element", json!({"features": "none"}))]),
+        ]);
+
+        assert_eq!(merged[0].value["features"], json!("none"));
+        assert_eq!(merged[0].fields["features"], "private");
+        assert!(!merged[0].fields.contains_key("features.e2ee"));
+    }
+
+    #[test]
+    fn hides_and_shows_projects_across_layers() {
+        let element = ("element", json!({"title": "Element"}));
+
+        let (merged, _) = merge_entries(&[
+            ("data", vec![element.clone()]),
+            ("private", vec![("element", json!({"hidden": true}))]),
+        ]);
+        assert_eq!(merged[0].hidden_by.as_deref(), Some("private"));
+        assert!(!merged[0].value.contains_key(HIDDEN_KEY));
+
+        let (merged, _) = merge_entries(&[
+            ("data", vec![element.clone()]),
+            ("private", vec![("element", json!({"hidden": true}))]),
+            ("staging", vec![("element", json!({"hidden": false}))]),
+        ]);
+        assert_eq!(merged[0].hidden_by, None);
+
+        // A layer that doesn't mention `hidden` keeps the project hidden
+        let (merged, _) = merge_entries(&[
+            ("data", vec![element]),
+            ("private", vec![("element", json!({"hidden": true}))]),
+            (
+                "staging",
+                vec![("element", json!({"home": "https://element.io"}))],
+            ),
+        ]);
+        assert_eq!(merged[0].hidden_by.as_deref(), Some("private"));
+    }
+
+    #[test]
+    fn rejects_a_hidden_key_that_is_not_a_boolean() {
+        let (merged, errors) = merge_entries(&[
+            ("data", vec![("element", json!({"title": "Element"}))]),
+            ("private", vec![("element", json!({"hidden": "true"}))]),
+        ]);
+
+        assert_eq!(merged[0].hidden_by, None);
+        assert_eq!(
+            errors,
+            vec!["private/element.toml: hidden should be true or false"]
+        );
+    }
+
+    #[test]
+    fn rejects_fields_an_overlay_cannot_set() {
+        let (_, errors) = merge_entries(&[
+            ("data", vec![("element", json!({"title": "Element"}))]),
+            (
+                "private",
+                vec![(
+                    "element",
+                    json!({"hiden": true, "home": "https://element.io"}),
+                )],
+            ),
+        ]);
+
+        assert_eq!(
+            errors,
+            vec!["private/element.toml: client has no field hiden"]
+        );
+    }
+
+    #[test]
+    fn adds_projects_defined_by_an_overlay() {
+        let (merged, _) = merge_entries(&[
+            ("data", vec![("element", json!({"title": "Element"}))]),
+            (
+                "private",
+                vec![("embargoed", json!({"title": "Embargoed"}))],
+            ),
+        ]);
+
+        let ids: Vec<&str> = merged.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["element", "embargoed"]);
+        assert!(merged[0].overlays.is_empty());
+        assert_eq!(merged[1].overlays, vec!["private"]);
+    }
+
+    #[test]
+    fn rejects_duplicate_ids_in_a_layer() {
+        let (merged, errors) = merge_entries(&[(
+            "data",
+            vec![
+                ("element", json!({"title": "Element"})),
+                ("element", json!({"title": "Element X"})),
+            ],
+        )]);
+
+        assert_eq!(merged[0].value["title"], json!("Element"));
+        assert_eq!(
+            errors,
+            vec!["data/element.toml: client element is already defined in data"]
+        );
+    }
+
+    #[test]
+    fn the_same_id_in_two_layers_is_not_a_duplicate() {
+        let (merged, errors) = merge_entries(&[
+            ("data", vec![("element", json!({"title": "Element"}))]),
+            ("private", vec![("element", json!({"title": "Element X"}))]),
+        ]);
+
+        assert!(errors.is_empty());
+        assert_eq!(merged[0].value["title"], json!("Element X"));
+        assert_eq!(
+            merged[0].paths,
+            vec![
+                PathBuf::from("data/element.toml"),
+                PathBuf::from("private/element.toml")
+            ]
+        );
+    }
+}
@@ -1,123 +1,416 @@
-use std::{fs, path::Path};
-
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use itertools::Itertools;
+use serde::de::DeserializeOwned;
+
+use crate::author_index::AuthorIndex;
+use crate::bot::Bot;
+use crate::bridge::Bridge;
 use crate::client::Client;
-
+use crate::export::Export;
+use crate::iot::Iot;
+use crate::msc_index::MscIndex;
+use crate::other::Other;
+use crate::output::Output;
+use crate::projects::{ProjectEntry, Projects};
+use crate::protocol::Protocol;
+use crate::sdk::Sdk;
+use crate::server::Server;
+use crate::settings::{SectionSettings, Settings};
+
+mod add;
+mod author_index;
 mod bot;
 mod bridge;
 mod client;
+mod comparison;
+mod data;
+mod export;
 mod iot;
+mod layers;
+mod migrate;
+mod msc_index;
 mod other;
+mod output;
 mod projects;
+mod prompt;
+mod protocol;
 mod sdk;
+mod sdk_usage;
 mod server;
+mod settings;
+mod templates;
 mod twim_config;
+mod validate;
+
+const SETTINGS_PATH: &str = "./squiddy.toml";
+const DATA_PATH: &str = "./data";
+const TWIM_CONFIG_PATH: &str = "../twim-config/config.toml";
+const MATRIXDOTORG_PROJECTS_PATH: &str = "../matrix.org/gatsby/content/projects";
+const MATRIXTO_PROJECTS_PATH: &str = "../matrix.to/src/open/clients";
+const JSON_EXPORT_PATH: &str = "./projects.json";
+const AUTHOR_INDEX_PATH: &str = "./authors.json";
+const MSC_INDEX_PATH: &str = "./mscs.json";
+const TEMPLATES_PATH: &str = "./doc";
+
+/// Keeps the Matrix ecosystem projects in sync across matrix.org, matrix.to and twim-config
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Data directory merged on top of the data and the overlays of squiddy.toml, can be repeated
+    #[arg(long, global = true)]
+    overlay: Vec<String>,
+}
 
-fn main() {
-    const PROJECT_DATA_PATH: &str = "./data/projects.toml";
-    const TWIM_CONFIG_PATH: &str = "../twim-config/config.toml";
-    const MATRIXDOTORG_PROJECTS_PATH: &str = "../matrix.org/gatsby/content/projects";
-    const MATRIXTO_PROJECTS_PATH: &str = "../matrix.to/src/open/clients";
-    let projects_file = fs::read(PROJECT_DATA_PATH).expect("Unable to open master data file");
+#[derive(Subcommand)]
+enum Command {
+    /// Generate the given targets from the data file, or all of them if none is given
+    Generate {
+        #[arg(value_enum)]
+        targets: Vec<Target>,
+        /// Delete the matrix.org pages of projects that are no longer in the data
+        #[arg(long)]
+        prune: bool,
+    },
+    /// Print the JSON Schema of the legacy data file, or of the project files of a category,
+    /// for editor autocompletion and CI validation
+    Schema {
+        #[arg(value_enum)]
+        category: Option<Category>,
+    },
+    /// Generate the templates of each category in the doc directory
+    Templates,
+    /// Rewrite the data files in their canonical form, skipping the ones with comments
+    Fmt {
+        /// Only check that the data files are formatted, failing if they aren't
+        #[arg(long)]
+        check: bool,
+    },
+    /// Show which data layer each field of the projects changed by an overlay comes from
+    Layers {
+        /// Show every project, including the ones no overlay changes
+        #[arg(long)]
+        all: bool,
+    },
+    /// Show how many projects of each category are built on each SDK
+    Sdks,
+    /// Show which clients, servers and SDKs implement each MSC, or only the given one
+    Mscs {
+        /// Number of the MSC, e.g. MSC3575
+        msc: Option<String>,
+    },
+    /// Upgrade the data and the overlays to the current version of the schema
+    Migrate,
+    /// Move each project of the legacy single data file to its own file
+    Split,
+    /// Add a new project to the data, asking for each of its fields
+    Add {
+        #[arg(value_enum)]
+        category: Category,
+        /// Read the entry from a TOML, YAML or JSON file instead of asking for it, `-` for stdin
+        #[arg(long)]
+        from: Option<PathBuf>,
+    },
+}
 
-    let projects: projects::Projects =
-        toml::from_slice(&projects_file).expect("Unable to parse master data file");
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Category {
+    Bot,
+    Bridge,
+    Client,
+    Iot,
+    Other,
+    Sdk,
+    Server,
+}
 
-    let twim_config_file = fs::read(TWIM_CONFIG_PATH).expect("Unable to open twim config file");
-    let mut twim_config: twim_config::Config =
-        toml::from_slice(&twim_config_file).expect("Unable to parse twim-config file");
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, ValueEnum)]
+enum Target {
+    /// Project pages of matrix.org
+    Matrixdotorg,
+    /// Client files of matrix.to
+    Matrixto,
+    /// Projects of the TWIM bot config
+    TwimConfig,
+    /// Machine-readable export of all the projects
+    Json,
+    /// Machine-readable index of the projects of each author
+    Authors,
+    /// Machine-readable index of the projects implementing each MSC
+    Mscs,
+}
 
-    let mut twim_projects_matched = 0;
-    let mut twim_projects_added = 0;
+fn main() {
+    let cli = Cli::parse();
+    let prune = matches!(cli.command, Some(Command::Generate { prune: true, .. }));
+
+    let targets = match cli.command {
+        Some(Command::Schema { category }) => {
+            let schema = match category {
+                None => schemars::schema_for!(Projects),
+                Some(Category::Bot) => data::entry_schema::<Bot>(),
+                Some(Category::Bridge) => data::entry_schema::<Bridge>(),
+                Some(Category::Client) => data::entry_schema::<Client>(),
+                Some(Category::Iot) => data::entry_schema::<Iot>(),
+                Some(Category::Other) => data::entry_schema::<Other>(),
+                Some(Category::Sdk) => data::entry_schema::<Sdk>(),
+                Some(Category::Server) => data::entry_schema::<Server>(),
+            };
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&schema).expect("Unable to serialize schema")
+            );
+            return;
+        }
+        Some(Command::Templates) => {
+            let mut output = Output::default();
+            write_template::<Bot>(&mut output);
+            write_template::<Bridge>(&mut output);
+            write_template::<Client>(&mut output);
+            write_template::<Iot>(&mut output);
+            write_template::<Other>(&mut output);
+            write_template::<Sdk>(&mut output);
+            write_template::<Server>(&mut output);
+            output.print_summary();
+            return;
+        }
+        Some(Command::Fmt { check }) => {
+            format_data(check);
+            return;
+        }
+        Some(Command::Layers { all }) => {
+            let settings = Settings::load(SETTINGS_PATH);
+            let (_, provenance) = layers::load(&data_layers(&settings, cli.overlay));
+            layers::print_report(&provenance, all);
+            return;
+        }
+        Some(Command::Sdks) => {
+            let settings = Settings::load(SETTINGS_PATH);
+            let (projects, _) = layers::load(&data_layers(&settings, cli.overlay));
+            sdk_usage::print_report(&projects);
+            return;
+        }
+        Some(Command::Mscs { msc }) => {
+            let settings = Settings::load(SETTINGS_PATH);
+            let (projects, _) = layers::load(&data_layers(&settings, cli.overlay));
+            MscIndex::new(&projects).print_report(msc.as_deref());
+            return;
+        }
+        Some(Command::Migrate) => {
+            let settings = Settings::load(SETTINGS_PATH);
+            let mut output = Output::default();
+            let mut migrated = 0;
+            for layer in data_layers(&settings, cli.overlay) {
+                migrated += data::migrate(&layer, &mut output);
+            }
+            match migrated {
+                0 => println!(
+                    "The data is already at {} {}",
+                    migrate::VERSION_KEY,
+                    migrate::CURRENT_VERSION
+                ),
+                _ => output.print_summary(),
+            }
+            return;
+        }
+        Some(Command::Split) => {
+            let mut output = Output::default();
+            data::split(DATA_PATH, &mut output);
+            output.print_summary();
+            return;
+        }
+        Some(Command::Add { category, from }) => {
+            match category {
+                Category::Bot => add_entry::<Bot>(from),
+                Category::Bridge => add_entry::<Bridge>(from),
+                Category::Client => add_entry::<Client>(from),
+                Category::Iot => add_entry::<Iot>(from),
+                Category::Other => add_entry::<Other>(from),
+                Category::Sdk => add_entry::<Sdk>(from),
+                Category::Server => add_entry::<Server>(from),
+            }
+            return;
+        }
+        None => vec![Target::Matrixdotorg, Target::Matrixto, Target::TwimConfig],
+        Some(Command::Generate { targets, .. }) if targets.is_empty() => {
+            Target::value_variants().to_vec()
+        }
+        Some(Command::Generate { targets, .. }) => targets,
+    };
 
-    println!(
-        "TWIM Config contains {} projects",
-        twim_config.projects.len()
-    );
+    let settings = Settings::load(SETTINGS_PATH);
 
-    for bot in &projects.bots {
-        let mut found = false;
-        for twim_project in &mut twim_config.projects {
-            if bot.title == twim_project.title {
-                println!("Found {} in data and twim-config", twim_project.title);
-                twim_projects_matched += 1;
-                *twim_project = twim_config::Project::from(bot);
-                found = true;
-                break;
+    let (projects, _) = layers::load(&data_layers(&settings, cli.overlay));
+
+    let mut output = Output::default();
+    for target in targets.into_iter().unique() {
+        match target {
+            Target::Matrixdotorg => generate_matrixdotorg(&projects, prune, &mut output),
+            Target::Matrixto => generate_matrixto(&projects, &mut output),
+            Target::TwimConfig => sync_twim_config(&projects, &settings, &mut output),
+            Target::Json => {
+                output.write("json", JSON_EXPORT_PATH, Export::new(&projects).to_json());
+            }
+            Target::Authors => {
+                output.write(
+                    "authors",
+                    AUTHOR_INDEX_PATH,
+                    AuthorIndex::new(&projects).to_json(),
+                );
+            }
+            Target::Mscs => {
+                output.write("mscs", MSC_INDEX_PATH, MscIndex::new(&projects).to_json());
             }
         }
+    }
 
-        if !found {
-            println!("Didn't find {} in twim-config, adding", bot.title);
-            twim_projects_added += 1;
-            twim_config.projects.push(twim_config::Project::from(bot));
-        }
+    output.print_summary();
+}
 
-        let matrixdotorg_project_path =
-            format!("{}/bots/{}", MATRIXDOTORG_PROJECTS_PATH, bot.filename());
-        fs::write(&matrixdotorg_project_path, bot.to_markdown()).unwrap_or_else(|_| {
-            panic!("Could not write project file {}", matrixdotorg_project_path)
-        });
+/// The data directory, then the overlays of the settings, then the ones of the command line.
+/// Exits if an overlay doesn't exist since running
+/// without it would publish what it hides
+fn data_layers(settings: &Settings, overlays: Vec<String>) -> Vec<String> {
+    let mut layers = vec![DATA_PATH.to_string()];
+    layers.extend(settings.overlays.iter().cloned());
+    layers.extend(overlays);
+
+    let missing = layers[1..]
+        .iter()
+        .filter(|layer| !Path::new(layer).is_dir())
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        for layer in missing {
+            eprintln!("The overlay {} is not a directory", layer);
+        }
+        process::exit(1);
     }
 
-    for bridge in &projects.bridges {
-        let mut found = false;
-        for twim_project in &mut twim_config.projects {
-            if bridge.title == twim_project.title {
-                println!("Found {} in data and twim-config", twim_project.title);
-                twim_projects_matched += 1;
-                *twim_project = twim_config::Project::from(bridge);
-                found = true;
-                break;
-            }
-        }
+    layers
+}
+
+fn format_data(check: bool) {
+    let mut output = Output::default();
+    let formatted = data::format(DATA_PATH, check, &mut output);
 
-        if !found {
-            println!("Didn't find {} in twim-config, adding", bridge.title);
-            twim_projects_added += 1;
-            twim_config
-                .projects
-                .push(twim_config::Project::from(bridge));
+    if check {
+        if !formatted {
+            eprintln!("Run `squiddy fmt` to format the data files");
+            process::exit(1);
         }
+        println!("Data files are formatted");
+    } else {
+        output.print_summary();
+    }
+}
 
-        let matrixdotorg_project_path = format!(
-            "{}/bridges/{}",
-            MATRIXDOTORG_PROJECTS_PATH,
-            bridge.filename()
-        );
-        fs::write(&matrixdotorg_project_path, bridge.to_markdown()).unwrap_or_else(|_| {
-            panic!("Could not write project file {}", matrixdotorg_project_path)
-        });
+fn add_entry<T: ProjectEntry + DeserializeOwned>(from: Option<PathBuf>) {
+    let entry: T = match from {
+        Some(path) => add::from_file(&path, DATA_PATH),
+        None => add::interactive(DATA_PATH),
+    };
+    let path = data::add(DATA_PATH, &entry);
+    println!("Added {} to {}", entry.title(), path.display());
+}
+
+fn write_template<T: ProjectEntry>(output: &mut Output) {
+    let path = format!("{}/template-{}.toml", TEMPLATES_PATH, T::CATEGORY);
+    output.write("templates", path, templates::template::<T>());
+}
+
+/// Writes the matrix.org pages, and with `prune` deletes the other pages of their directories
+fn generate_matrixdotorg(projects: &Projects, prune: bool, output: &mut Output) {
+    // Pruning against empty data would wipe every page
+    if prune && projects.is_empty() {
+        eprintln!("The data has no projects, refusing to prune the matrix.org pages");
+        process::exit(1);
     }
 
-    for client in &projects.clients {
-        let mut found = false;
-        for twim_project in &mut twim_config.projects {
-            if client.title == twim_project.title {
-                println!("Found {} in data and twim-config", twim_project.title);
-                twim_projects_matched += 1;
-                *twim_project = twim_config::Project::from(client);
-                found = true;
-                break;
-            }
-        }
+    write_matrixdotorg_pages(&projects.bots, prune, output);
+    write_matrixdotorg_pages(&projects.bridges, prune, output);
+    write_matrixdotorg_pages(&projects.clients, prune, output);
+    write_matrixdotorg_pages(&projects.iots, prune, output);
+    write_matrixdotorg_pages(&projects.others, prune, output);
+    write_matrixdotorg_pages(&projects.sdks, prune, output);
+    write_matrixdotorg_pages(&projects.servers, prune, output);
+    write_protocol_pages(projects, prune, output);
+    write_comparison_pages(projects, prune, output);
+}
 
-        if !found {
-            println!("Didn't find {} in twim-config, adding", client.title);
-            twim_projects_added += 1;
-            twim_config
-                .projects
-                .push(twim_config::Project::from(client));
-        }
+fn write_matrixdotorg_pages<T: ProjectEntry>(entries: &[T], prune: bool, output: &mut Output) {
+    let directory = format!("{}/{}", MATRIXDOTORG_PROJECTS_PATH, T::DIRECTORY);
 
-        let matrixdotorg_project_path = format!(
-            "{}/clients/{}",
-            MATRIXDOTORG_PROJECTS_PATH,
-            client.filename()
+    for entry in entries {
+        let matrixdotorg_project_path = format!("{}/{}", directory, entry.filename());
+        output.write(
+            "matrix.org",
+            &matrixdotorg_project_path,
+            entry.to_markdown(),
         );
-        fs::write(&matrixdotorg_project_path, client.to_markdown()).unwrap_or_else(|_| {
-            panic!("Could not write project file {}", matrixdotorg_project_path)
-        });
+    }
+
+    if prune {
+        output.remove_stale("matrix.org", &directory, "mdx");
+    }
+}
 
+fn write_protocol_pages(projects: &Projects, prune: bool, output: &mut Output) {
+    let directory = format!("{}/{}", MATRIXDOTORG_PROJECTS_PATH, protocol::DIRECTORY);
+    if projects.protocols.is_empty() && !Path::new(&directory).exists() {
+        return;
+    }
+    fs::create_dir_all(&directory)
+        .unwrap_or_else(|_| panic!("Could not create directory {}", directory));
+
+    for (id, bridges) in protocol::bridges_by_protocol(projects) {
+        let path = format!("{}/{}", directory, Protocol::filename(id));
+        output.write(
+            "matrix.org",
+            &path,
+            projects.protocols[id].to_markdown(&bridges),
+        );
+    }
+
+    if prune {
+        output.remove_stale("matrix.org", &directory, "mdx");
+    }
+}
+
+fn write_comparison_pages(projects: &Projects, prune: bool, output: &mut Output) {
+    let directory = format!("{}/{}", MATRIXDOTORG_PROJECTS_PATH, comparison::DIRECTORY);
+    let pages = [
+        Iot::index_page(&projects.iots).map(|page| (Iot::DIRECTORY, page)),
+        Sdk::comparison_table(&projects.sdks).map(|table| {
+            let page = comparison::page("SDKs", Sdk::CATEGORY, &table);
+            (Sdk::DIRECTORY, page)
+        }),
+        Server::comparison_table(&projects.servers).map(|table| {
+            let page = comparison::page("Homeservers", Server::CATEGORY, &table);
+            (Server::DIRECTORY, page)
+        }),
+    ];
+
+    for (name, page) in pages.into_iter().flatten() {
+        fs::create_dir_all(&directory)
+            .unwrap_or_else(|_| panic!("Could not create directory {}", directory));
+        let path = format!("{}/{}.mdx", directory, name);
+        output.write("matrix.org", &path, page);
+    }
+
+    if prune && Path::new(&directory).exists() {
+        output.remove_stale("matrix.org", &directory, "mdx");
+    }
+}
+
+fn generate_matrixto(projects: &Projects, output: &mut Output) {
+    for client in &projects.clients {
         let matrixto_file_path = format!(
             "{}/{}.js",
             MATRIXTO_PROJECTS_PATH,
@@ -129,147 +422,128 @@ fn main() {
             MATRIXTO_PROJECTS_PATH,
             client.matrixto_filename()
         );
-        fs::write(
+        output.write(
+            "matrix.to",
             &matrixto_data_file_path,
             Client::matrixto_join_file(client.id.clone(), projects.clients.clone()),
-        )
-        .unwrap_or_else(|_| {
-            panic!(
-                "Could not write project data file {}",
-                matrixto_data_file_path
-            )
-        });
-
-        if !Path::new(&matrixto_file_path).exists() {
-            fs::write(&matrixto_file_path, client.matrixto_template_file())
-                .unwrap_or_else(|_| panic!("Could not write project file {}", matrixto_file_path));
-        }
+        );
+
+        output.write_if_missing(
+            "matrix.to",
+            &matrixto_file_path,
+            client.matrixto_template_file(),
+        );
     }
 
     let matrixto_index_path = format!("{}/index.js", MATRIXTO_PROJECTS_PATH);
-    fs::write(
+    output.write(
+        "matrix.to",
         &matrixto_index_path,
-        Client::matrixto_index((&projects.clients).clone()),
-    )
-    .unwrap_or_else(|_| panic!("Could not write matrix.to index.js file"));
-
-    for iot in &projects.iots {
-        let mut found = false;
-        for twim_project in &mut twim_config.projects {
-            if iot.title == twim_project.title {
-                println!("Found {} in data and twim-config", twim_project.title);
-                twim_projects_matched += 1;
-                *twim_project = twim_config::Project::from(iot);
-                found = true;
-                break;
-            }
-        }
-
-        if !found {
-            println!("Didn't find {} in twim-config, adding", iot.title);
-            twim_projects_added += 1;
-            twim_config.projects.push(twim_config::Project::from(iot));
-        }
-
-        let matrixdotorg_project_path =
-            format!("{}/iot/{}", MATRIXDOTORG_PROJECTS_PATH, iot.filename());
-        fs::write(&matrixdotorg_project_path, iot.to_markdown()).unwrap_or_else(|_| {
-            panic!("Could not write project file {}", matrixdotorg_project_path)
-        });
-    }
+        Client::matrixto_index(projects.clients.clone()),
+    );
+}
 
-    for other in &projects.others {
-        let mut found = false;
-        for twim_project in &mut twim_config.projects {
-            if other.title == twim_project.title {
-                println!("Found {} in data and twim-config", twim_project.title);
-                twim_projects_matched += 1;
-                *twim_project = twim_config::Project::from(other);
-                found = true;
-                break;
-            }
-        }
+fn sync_twim_config(projects: &Projects, settings: &Settings, output: &mut Output) {
+    let twim_config_file = fs::read(TWIM_CONFIG_PATH).expect("Unable to open twim config file");
+    let twim_config_file =
+        String::from_utf8(twim_config_file).expect("twim-config file is not valid UTF-8");
+    let mut twim_config: twim_config::Config =
+        toml::from_str(&twim_config_file).expect("Unable to parse twim-config file");
 
-        if !found {
-            println!("Didn't find {} in twim-config, adding", other.title);
-            twim_projects_added += 1;
-            twim_config.projects.push(twim_config::Project::from(other));
-        }
+    let mut counts = TwimCounts::default();
 
-        let matrixdotorg_project_path =
-            format!("{}/other/{}", MATRIXDOTORG_PROJECTS_PATH, other.filename());
-        fs::write(&matrixdotorg_project_path, other.to_markdown()).unwrap_or_else(|_| {
-            panic!("Could not write project file {}", matrixdotorg_project_path)
-        });
-    }
+    println!(
+        "TWIM Config contains {} projects",
+        twim_config.projects.len()
+    );
 
-    for sdk in &projects.sdks {
-        let mut found = false;
-        for twim_project in &mut twim_config.projects {
-            if sdk.title == twim_project.title {
-                println!("Found {} in data and twim-config", twim_project.title);
-                twim_projects_matched += 1;
-                *twim_project = twim_config::Project::from(sdk);
-                found = true;
-                break;
+    let config = &mut twim_config;
+    let sections = &settings.sections;
+    sync_twim_projects(config, &projects.bots, &sections.bots, &mut counts);
+    sync_twim_projects(config, &projects.bridges, &sections.bridges, &mut counts);
+    sync_twim_projects(config, &projects.clients, &sections.clients, &mut counts);
+    sync_twim_projects(config, &projects.iots, &sections.iots, &mut counts);
+    sync_twim_projects(config, &projects.others, &sections.others, &mut counts);
+    sync_twim_projects(config, &projects.sdks, &sections.sdks, &mut counts);
+    sync_twim_projects(config, &projects.servers, &sections.servers, &mut counts);
+
+    twim_config.sort_projects();
+    for section in twim_config.missing_sections() {
+        let question = format!(
+            "Section {} doesn't exist in twim-config, create it?",
+            section
+        );
+        match settings.sections.iter().find(|s| s.name == section) {
+            Some(section_settings) if prompt::confirm(&question) => {
+                println!("Adding section {} to twim-config", section);
+                twim_config
+                    .sections
+                    .push(twim_config::Section::from(section_settings));
             }
+            _ => println!(
+                "Warning: section {} is used by projects but doesn't exist in twim-config",
+                section
+            ),
         }
-
-        if !found {
-            println!("Didn't find {} in twim-config, adding", sdk.title);
-            twim_projects_added += 1;
-            twim_config.projects.push(twim_config::Project::from(sdk));
-        }
-
-        let matrixdotorg_project_path =
-            format!("{}/sdks/{}", MATRIXDOTORG_PROJECTS_PATH, sdk.filename());
-        fs::write(&matrixdotorg_project_path, sdk.to_markdown()).unwrap_or_else(|_| {
-            panic!("Could not write project file {}", matrixdotorg_project_path)
-        });
     }
 
-    for server in &projects.servers {
-        let mut found = false;
-        for twim_project in &mut twim_config.projects {
-            if server.title == twim_project.title {
-                println!("Found {} in data and twim-config", twim_project.title);
-                twim_projects_matched += 1;
-                *twim_project = twim_config::Project::from(server);
-                found = true;
-                break;
-            }
-        }
-
-        if !found {
-            println!("Didn't find {} in twim-config, adding", server.title);
-            twim_projects_added += 1;
-            twim_config
-                .projects
-                .push(twim_config::Project::from(server));
-        }
+    for (emoji, names) in twim_config.emoji_collisions() {
+        println!(
+            "Warning: emoji {} is used by several sections or projects: {}",
+            emoji,
+            names.join(", ")
+        );
+    }
 
-        let matrixdotorg_project_path = format!(
-            "{}/servers/{}",
-            MATRIXDOTORG_PROJECTS_PATH,
-            server.filename()
+    let placeholders = twim_config.projects_with_placeholder_emoji();
+    if !placeholders.is_empty() {
+        println!(
+            "{} projects still have a placeholder emoji, set twim_emoji in the data file:",
+            placeholders.len()
         );
-        fs::write(&matrixdotorg_project_path, server.to_markdown()).unwrap_or_else(|_| {
-            panic!("Could not write project file {}", matrixdotorg_project_path)
-        });
+        for project in placeholders {
+            println!("  - {} ({})", project.title, project.emoji);
+        }
     }
 
-    fs::write(TWIM_CONFIG_PATH, toml::to_string(&twim_config).unwrap())
-        .expect("Unable to write to twim-config");
+    output.write(
+        "twim-config",
+        TWIM_CONFIG_PATH,
+        twim_config.to_document(&twim_config_file),
+    );
 
     println!(
         "{} of them are not known in the meta repository",
-        twim_config.projects.len() - twim_projects_matched
+        twim_config.projects.len() - counts.matched
     );
 
     println!(
         "TWIM-Config now contains {} projects",
         twim_config.projects.len()
     );
-    println!("{} of them were updated", twim_projects_matched);
-    println!("{} were just added", twim_projects_added);
+    println!("{} of them were updated", counts.matched);
+    println!("{} were just added", counts.added);
+}
+
+#[derive(Default)]
+struct TwimCounts {
+    matched: usize,
+    added: usize,
+}
+
+fn sync_twim_projects<T: ProjectEntry>(
+    twim_config: &mut twim_config::Config,
+    entries: &[T],
+    section: &SectionSettings,
+    counts: &mut TwimCounts,
+) where
+    for<'a> twim_config::Project: From<(&'a T, &'a SectionSettings)>,
+{
+    for entry in entries {
+        if twim_config.upsert(twim_config::Project::from((entry, section))) {
+            counts.matched += 1;
+        } else {
+            counts.added += 1;
+        }
+    }
 }
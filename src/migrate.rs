@@ -0,0 +1,129 @@
+use serde_json::{Map, Value as JsonValue};
+
+/// Version of the data layout this squiddy reads and writes, to bump along with a new
+/// migration whenever a field is renamed, removed or changes shape
+pub const CURRENT_VERSION: u32 = 1;
+
+/// Version of the data files written before they carried a version
+pub const UNVERSIONED: u32 = 1;
+
+/// Top-level key giving the version of a data file
+pub const VERSION_KEY: &str = "schema_version";
+
+/// Upgrade of an entry from one version to the next one
+struct Migration {
+    from: u32,
+    description: &'static str,
+    /// Applied to each entry with the key of its category (e.g. `clients`). Entries of
+    /// overlays only hold the fields they change, any field can be missing.
+    apply: fn(category: &str, entry: &mut Map<String, JsonValue>),
+}
+
+/// Every migration, in order
+const MIGRATIONS: &[Migration] = &[];
+
+/// Version of a data file, from its top-level key. Fails if this squiddy doesn't know it yet.
+pub fn version_of(file: &JsonValue) -> Result<u32, String> {
+    let version = match file.get(VERSION_KEY) {
+        None => UNVERSIONED,
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| format!("{} should be a positive number", VERSION_KEY))?,
+    };
+
+    if version > CURRENT_VERSION {
+        return Err(format!(
+            "{} {} is newer than the {} this squiddy knows about, update it",
+            VERSION_KEY, version, CURRENT_VERSION
+        ));
+    }
+    Ok(version)
+}
+
+/// Upgrades an entry written with the given version to the current one, returns what was changed
+pub fn upgrade(category: &str, entry: &mut JsonValue, version: u32) -> Vec<&'static str> {
+    apply_migrations(MIGRATIONS, category, entry, version)
+}
+
+fn apply_migrations(
+    migrations: &[Migration],
+    category: &str,
+    entry: &mut JsonValue,
+    version: u32,
+) -> Vec<&'static str> {
+    let Some(entry) = entry.as_object_mut() else {
+        return vec![];
+    };
+
+    let mut applied = vec![];
+    for migration in migrations.iter().filter(|m| m.from >= version) {
+        (migration.apply)(category, entry);
+        applied.push(migration.description);
+    }
+    applied
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// Migration of a made-up version 2 where `maturity` is renamed `stage` in clients
+    const RENAME_MATURITY: &[Migration] = &[Migration {
+        from: 1,
+        description: "clients: renamed maturity to stage",
+        apply: |category, entry| {
+            if category != "clients" {
+                return;
+            }
+            if let Some(maturity) = entry.remove("maturity") {
+                entry.insert("stage".to_string(), maturity);
+            }
+        },
+    }];
+
+    #[test]
+    fn upgrades_from_1_to_2() {
+        let mut entry = json!({"title": "Element", "maturity": "Stable"});
+        let applied = apply_migrations(RENAME_MATURITY, "clients", &mut entry, 1);
+
+        assert_eq!(applied, vec!["clients: renamed maturity to stage"]);
+        assert_eq!(entry, json!({"title": "Element", "stage": "Stable"}));
+    }
+
+    #[test]
+    fn leaves_partial_and_other_entries_alone() {
+        let mut overlay = json!({"slug": "element", "home": "https://element.io"});
+        apply_migrations(RENAME_MATURITY, "clients", &mut overlay, 1);
+        assert_eq!(
+            overlay,
+            json!({"slug": "element", "home": "https://element.io"})
+        );
+
+        let mut bot = json!({"title": "Botty", "maturity": "Beta"});
+        apply_migrations(RENAME_MATURITY, "bots", &mut bot, 1);
+        assert_eq!(bot, json!({"title": "Botty", "maturity": "Beta"}));
+    }
+
+    #[test]
+    fn skips_migrations_older_than_the_entry() {
+        let mut entry = json!({"title": "Element", "maturity": "Stable"});
+        let applied = apply_migrations(RENAME_MATURITY, "clients", &mut entry, 2);
+
+        assert!(applied.is_empty());
+        assert_eq!(entry, json!({"title": "Element", "maturity": "Stable"}));
+    }
+
+    #[test]
+    fn reads_the_version_of_a_file() {
+        assert_eq!(version_of(&json!({"clients": []})), Ok(UNVERSIONED));
+        assert_eq!(
+            version_of(&json!({VERSION_KEY: CURRENT_VERSION})),
+            Ok(CURRENT_VERSION)
+        );
+        assert!(version_of(&json!({VERSION_KEY: CURRENT_VERSION + 1})).is_err());
+        assert!(version_of(&json!({VERSION_KEY: "one"})).is_err());
+    }
+}
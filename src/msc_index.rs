@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::projects::{MscStatus, ProjectEntry, Projects};
+
+/// Version of the MSC index format, to bump whenever a field is removed or changes meaning.
+/// See `doc/msc-index.md` for the description of the format.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Machine-readable document listing the projects implementing each Matrix Spec Change
+#[derive(Serialize, Debug)]
+pub struct MscIndex {
+    pub version: u32,
+    pub mscs: Vec<IndexedMsc>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct IndexedMsc {
+    pub msc: String,
+    pub projects: Vec<IndexedProject>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct IndexedProject {
+    pub slug: String,
+    pub category: &'static str,
+    pub title: String,
+    pub matrixdotorg_url: String,
+    pub status: MscStatus,
+}
+
+impl MscIndex {
+    pub fn new(projects: &Projects) -> Self {
+        // Keyed by number so that MSC999 comes before MSC1000
+        let mut mscs = BTreeMap::new();
+        push_all(&mut mscs, &projects.clients);
+        push_all(&mut mscs, &projects.sdks);
+        push_all(&mut mscs, &projects.servers);
+
+        MscIndex {
+            version: FORMAT_VERSION,
+            mscs: mscs.into_values().collect(),
+        }
+    }
+
+    /// Renders the index, with MSCs sorted by number and their projects by category then slug
+    /// so that it can be diffed across runs
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("Unable to serialize MSC index");
+        json.push('\n');
+        json
+    }
+
+    /// Prints the projects implementing each MSC, or only the given one, most advanced first
+    pub fn print_report(&self, msc: Option<&str>) {
+        let mscs = self
+            .mscs
+            .iter()
+            .filter(|m| msc.is_none_or(|msc| m.msc.eq_ignore_ascii_case(msc)))
+            .collect::<Vec<_>>();
+
+        if mscs.is_empty() {
+            match msc {
+                Some(msc) => println!("No project implements {}", msc),
+                None => println!("No project gives the MSCs it implements"),
+            }
+            return;
+        }
+
+        for indexed in mscs {
+            println!("{}:", indexed.msc);
+            let projects = indexed
+                .projects
+                .iter()
+                .sorted_by(|a, b| (b.status, &a.title).cmp(&(a.status, &b.title)));
+            for project in projects {
+                println!(
+                    "  {} ({}): {}",
+                    project.title, project.category, project.status
+                );
+            }
+        }
+    }
+}
+
+fn push_all<T: ProjectEntry>(mscs: &mut BTreeMap<u32, IndexedMsc>, entries: &[T]) {
+    let mut entries: Vec<&T> = entries.iter().collect();
+    entries.sort_by_key(|entry| entry.slug());
+
+    for entry in entries {
+        for support in entry.msc_support() {
+            let number: u32 = support
+                .msc
+                .trim_start_matches("MSC")
+                .parse()
+                .expect("MSC numbers are checked when the data is loaded");
+            let indexed = mscs.entry(number).or_insert_with(|| IndexedMsc {
+                msc: support.msc.clone(),
+                projects: vec![],
+            });
+            indexed.projects.push(IndexedProject {
+                slug: entry.slug(),
+                category: T::CATEGORY,
+                title: entry.title().to_string(),
+                matrixdotorg_url: entry.matrixdotorg_url(),
+                status: support.status,
+            });
+        }
+    }
+}
@@ -1,30 +1,123 @@
 use convert_case::{Case, Casing};
-use indoc::formatdoc;
+use indoc::{formatdoc, indoc};
 use itertools::Itertools;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::projects::Author;
+use crate::projects::{self, Author, Maturity, ProjectEntry, ProjectLink};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct Other {
     pub title: String,
+    /// One-line summary of the project
     pub description: String,
     pub authors: Vec<Author>,
+    /// Can be Obsolete, Alpha, Beta or Stable
+    #[schemars(with = "Maturity")]
     pub maturity: String,
     pub language: String,
+    /// Has to be one of https://spdx.org/licenses/
     pub license: String,
     pub repository: Option<String>,
     pub home: Option<String>,
     pub screenshot: Option<String>,
     pub icon: Option<String>,
+    /// Alias of the room to discuss the project
     pub room: Option<String>,
+    /// SDKs the project is built on, slugs or titles of SDK entries
+    #[serde(default)]
+    pub sdk: Vec<String>,
+    /// Pages of the SDKs of `sdk` that are in the data
+    #[serde(skip)]
+    pub sdk_links: Vec<ProjectLink>,
     pub featured: bool,
     pub sort_order: Option<i32>,
+    /// Emoji the TWIM bot uses for the project
+    pub twim_emoji: Option<String>,
+    /// Multi-line description, supports markdown
+    #[schemars(extend("x-multiline" = true))]
     pub full_description: String,
 }
 
-impl Other {
-    pub fn to_markdown(&self) -> String {
+impl ProjectEntry for Other {
+    const CATEGORY: &'static str = "other";
+    const KEY: &'static str = "others";
+    const DIRECTORY: &'static str = "other";
+
+    fn example() -> Self {
+        Other {
+            title: "Matrix Mapping".to_string(),
+            description: "A project to crawl the whole Matrix public federation".to_string(),
+            authors: vec![
+                Author {
+                    name: "Fred".to_string(),
+                    matrix_id: Some("@fred:server.tld".to_string()),
+                    ..Default::default()
+                },
+                Author {
+                    name: "The Matrix Mapping Project".to_string(),
+                    matrix_id: None,
+                    ..Default::default()
+                },
+            ],
+            maturity: "Beta".to_string(),
+            language: "TypeScript".to_string(),
+            license: "AGPL-3.0-or-later".to_string(),
+            repository: Some("https://git.server.tld/mamap".to_string()),
+            home: Some("https://mamap.tld".to_string()),
+            screenshot: Some("/data/mamap-screenshot.png".to_string()),
+            icon: Some("/data/mamap-icon.svg".to_string()),
+            room: Some("#mamap:server.tld".to_string()),
+            sdk: vec!["matrix-js-sdk".to_string()],
+            sdk_links: vec![],
+            featured: false,
+            sort_order: Some(1),
+            twim_emoji: Some("🗺️".to_string()),
+            full_description: indoc! {"
+                A multi-line description that supports markdown to describe the project extensively.
+
+                For example, mamap can:
+                * crawl rooms
+                * link them together as it finds new room aliases
+                * generate a fancy graph of how rooms were discovered
+            "}
+            .to_string(),
+        }
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn authors(&self) -> &[Author] {
+        &self.authors
+    }
+
+    fn authors_mut(&mut self) -> &mut Vec<Author> {
+        &mut self.authors
+    }
+
+    fn license(&self) -> &str {
+        &self.license
+    }
+
+    fn room(&self) -> Option<&str> {
+        self.room.as_deref()
+    }
+
+    fn sdk(&self) -> &[String] {
+        &self.sdk
+    }
+
+    fn set_sdk_links(&mut self, links: Vec<ProjectLink>) {
+        self.sdk_links = links;
+    }
+
+    fn sort_order(&self) -> Option<i32> {
+        self.sort_order
+    }
+
+    fn to_markdown(&self) -> String {
         let layout = match self.icon {
             Some(_) => "projectimage",
             None => "project",
@@ -48,6 +141,8 @@ impl Other {
             self.icon.as_ref().map(|i| format!("thumbnail: {i}")),
             self.room.as_ref().map(|r| format!("room: \"{r}\"")),
             self.sort_order.as_ref().map(|o| format!("sort_order: {o}")),
+            projects::front_matter_list("sdk", &self.sdk),
+            ProjectLink::front_matter("sdk_links", &self.sdk_links),
         ]
         .iter()
         .flatten()
@@ -73,7 +168,7 @@ impl Other {
         "}
     }
 
-    pub fn filename(&self) -> String {
+    fn filename(&self) -> String {
         format!("{}.mdx", self.title.to_case(Case::Kebab))
     }
 }
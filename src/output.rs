@@ -0,0 +1,124 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileStatus {
+    Created,
+    Updated,
+    Unchanged,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Summary {
+    pub created: u32,
+    pub updated: u32,
+    pub unchanged: u32,
+    pub deleted: u32,
+}
+
+/// Writes generated files to disk, only touching the ones whose content actually changed
+/// so that mtimes and `git status` stay clean in the target repositories.
+#[derive(Debug, Default)]
+pub struct Output {
+    targets: Vec<(String, Summary)>,
+    written: HashSet<PathBuf>,
+}
+
+impl Output {
+    pub fn write(
+        &mut self,
+        target: &str,
+        path: impl AsRef<Path>,
+        contents: impl AsRef<[u8]>,
+    ) -> FileStatus {
+        let path = path.as_ref();
+        let contents = contents.as_ref();
+
+        let status = match fs::read(path) {
+            Ok(existing) if existing == contents => FileStatus::Unchanged,
+            Ok(_) => FileStatus::Updated,
+            Err(_) => FileStatus::Created,
+        };
+
+        if status != FileStatus::Unchanged {
+            fs::write(path, contents)
+                .unwrap_or_else(|_| panic!("Could not write file {}", path.display()));
+        }
+
+        // Several entries can render to the same file (e.g. clients sharing a matrix.to id),
+        // only count it once per run
+        if self.written.insert(path.to_path_buf()) {
+            let summary = self.summary_mut(target);
+            match status {
+                FileStatus::Created => summary.created += 1,
+                FileStatus::Updated => summary.updated += 1,
+                FileStatus::Unchanged => summary.unchanged += 1,
+            }
+        }
+
+        status
+    }
+
+    /// Writes the file only if it doesn't exist yet, leaving hand-edited files alone.
+    pub fn write_if_missing(
+        &mut self,
+        target: &str,
+        path: impl AsRef<Path>,
+        contents: impl AsRef<[u8]>,
+    ) -> FileStatus {
+        let path = path.as_ref();
+        if path.exists() {
+            if self.written.insert(path.to_path_buf()) {
+                self.summary_mut(target).unchanged += 1;
+            }
+            FileStatus::Unchanged
+        } else {
+            self.write(target, path, contents)
+        }
+    }
+
+    /// Deletes the files with the given extension in `dir` that were not written during this run,
+    /// only called with `generate --prune` since the directory can hold hand-written files.
+    pub fn remove_stale(&mut self, target: &str, dir: impl AsRef<Path>, extension: &str) {
+        let dir = dir.as_ref();
+        let entries = fs::read_dir(dir)
+            .unwrap_or_else(|_| panic!("Could not read directory {}", dir.display()));
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(extension)
+                || self.written.contains(&path)
+            {
+                continue;
+            }
+
+            println!("Deleting stale file {}", path.display());
+            fs::remove_file(&path)
+                .unwrap_or_else(|_| panic!("Could not delete file {}", path.display()));
+            self.summary_mut(target).deleted += 1;
+        }
+    }
+
+    pub fn print_summary(&self) {
+        for (target, summary) in &self.targets {
+            println!(
+                "{}: {} created, {} updated, {} unchanged, {} deleted",
+                target, summary.created, summary.updated, summary.unchanged, summary.deleted
+            );
+        }
+    }
+
+    fn summary_mut(&mut self, target: &str) -> &mut Summary {
+        let index = match self.targets.iter().position(|(t, _)| t == target) {
+            Some(index) => index,
+            None => {
+                self.targets.push((target.to_string(), Summary::default()));
+                self.targets.len() - 1
+            }
+        };
+        &mut self.targets[index].1
+    }
+}
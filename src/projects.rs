@@ -1,22 +1,234 @@
-use std::fmt::{self, Display, Formatter};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
 
 use crate::bot::Bot;
 use crate::bridge::Bridge;
 use crate::client::Client;
 use crate::iot::Iot;
 use crate::other::Other;
+use crate::protocol::Protocol;
 use crate::sdk::Sdk;
 use crate::server::Server;
-use serde::{Deserialize, Serialize};
+use crate::{migrate, templates, validate};
+use itertools::Itertools;
+use schemars::{JsonSchema, Schema, SchemaGenerator};
+use serde::{de, Deserialize, Deserializer, Serialize};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// What every category of projects can do
+pub trait ProjectEntry: Serialize + JsonSchema {
+    /// Category as it appears in the matrix.org front matter and exports
+    const CATEGORY: &'static str;
+    /// Key of the category in the data file
+    const KEY: &'static str;
+    /// Directory the matrix.org pages of the category are written to
+    const DIRECTORY: &'static str;
+
+    /// Entry showing every field, used to generate the templates
+    fn example() -> Self
+    where
+        Self: Sized;
+    fn title(&self) -> &str;
+    fn authors(&self) -> &[Author];
+    fn authors_mut(&mut self) -> &mut Vec<Author>;
+    fn license(&self) -> &str;
+    fn room(&self) -> Option<&str>;
+
+    /// Names of the SDKs the project is built on
+    fn sdk(&self) -> &[String] {
+        &[]
+    }
+
+    /// Sets the pages of the SDKs of `sdk` that are in the data
+    fn set_sdk_links(&mut self, _links: Vec<ProjectLink>) {}
+
+    /// Matrix Spec Change proposals the project implements, or plans to
+    fn msc_support(&self) -> &[MscSupport] {
+        &[]
+    }
+
+    fn sort_order(&self) -> Option<i32>;
+    fn to_markdown(&self) -> String;
+    fn filename(&self) -> String;
+
+    /// Problems with the entry that can't be expressed in its type
+    fn validate(&self) -> Vec<String>
+    where
+        Self: Sized,
+    {
+        validate::entry(self)
+    }
+
+    /// Identifier of the project page, derived from its filename
+    fn slug(&self) -> String {
+        self.filename().trim_end_matches(".mdx").to_string()
+    }
+
+    fn matrixdotorg_url(&self) -> String {
+        format!(
+            "https://matrix.org/docs/projects/{}/{}",
+            Self::CATEGORY,
+            self.slug()
+        )
+    }
+}
+
+/// Author of a project, written in the entry or referenced by its id in the author registry
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(from = "AuthorEntry", into = "AuthorEntry")]
 pub struct Author {
+    /// Id in the registry, if the entry references the author
+    pub id: Option<String>,
     pub name: String,
     pub matrix_id: Option<String>,
+    pub website: Option<String>,
+    pub organisation: Option<String>,
+}
+
+impl Author {
+    pub fn from_details(id: Option<String>, details: AuthorDetails) -> Self {
+        Author {
+            id,
+            name: details.name,
+            matrix_id: details.matrix_id,
+            website: details.website,
+            organisation: details.organisation,
+        }
+    }
+
+    /// Whether the author is a reference that wasn't resolved against the registry yet
+    pub fn is_unresolved(&self) -> bool {
+        self.id.is_some() && self.name.is_empty()
+    }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// How an author is written in a project entry
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+#[serde(untagged)]
+enum AuthorEntry {
+    Inline(AuthorDetails),
+    /// Id of the author in the registry
+    Id(String),
+}
+
+impl From<AuthorEntry> for Author {
+    fn from(entry: AuthorEntry) -> Self {
+        match entry {
+            AuthorEntry::Inline(details) => Author::from_details(None, details),
+            AuthorEntry::Id(id) => Author {
+                id: Some(id),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl From<Author> for AuthorEntry {
+    // References are written back as such until they are resolved
+    fn from(author: Author) -> Self {
+        match author.id {
+            Some(id) if author.name.is_empty() => AuthorEntry::Id(id),
+            _ => AuthorEntry::Inline(AuthorDetails {
+                name: author.name,
+                matrix_id: author.matrix_id,
+                website: author.website,
+                organisation: author.organisation,
+            }),
+        }
+    }
+}
+
+impl JsonSchema for Author {
+    fn schema_name() -> Cow<'static, str> {
+        "Author".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        AuthorEntry::json_schema(generator)
+    }
+}
+
+/// Person or organisation, in an entry or in the author registry
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct AuthorDetails {
+    pub name: String,
+    pub matrix_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub website: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organisation: Option<String>,
+}
+
+/// Page on matrix.org, computed from the data rather than written in it
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProjectLink {
+    pub category: &'static str,
+    pub title: String,
+    pub url: String,
+}
+
+impl ProjectLink {
+    /// Front matter list of the links under the given key, or nothing if there are none
+    pub fn front_matter(key: &str, links: &[ProjectLink]) -> Option<String> {
+        if links.is_empty() {
+            return None;
+        }
+        let items = links
+            .iter()
+            .map(|l| {
+                format!(
+                    "    - title: {}\n      category: {}\n      url: {}",
+                    l.title, l.category, l.url
+                )
+            })
+            .join("\n");
+        Some(format!("{}:\n{}", key, items))
+    }
+}
+
+/// Front matter string holding any text, quoted and escaped. YAML double-quoted strings
+/// share the escapes of JSON.
+pub fn front_matter_string(text: &str) -> String {
+    serde_json::to_string(text).expect("Unable to quote front matter string")
+}
+
+/// Front matter list of the given items, or nothing if there are none
+pub fn front_matter_list(key: &str, items: &[String]) -> Option<String> {
+    if items.is_empty() {
+        return None;
+    }
+    let items = items.iter().map(|i| format!("    - {}", i)).join("\n");
+    Some(format!("{}:\n{}", key, items))
+}
+
+/// Front matter of the features whose support is known, or nothing if none is
+pub fn features_front_matter(levels: &[(&str, Option<SupportLevel>)]) -> Option<String> {
+    let known = levels
+        .iter()
+        .filter_map(|(label, level)| Some(format!("    {}: {}", label, (*level)?)))
+        .collect::<Vec<_>>();
+    if known.is_empty() {
+        None
+    } else {
+        Some(format!("features:\n{}", known.join("\n")))
+    }
+}
+
+/// Master data file listing every project of the ecosystem
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, Default)]
+#[serde(default)]
 pub struct Projects {
+    /// Version of the layout of the data, `squiddy migrate` upgrades older data
+    #[serde(default = "unversioned")]
+    pub schema_version: u32,
+    /// Author registry, by id
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub authors: BTreeMap<String, AuthorDetails>,
+    /// Protocol registry of the networks bridges connect to, by id
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub protocols: BTreeMap<String, Protocol>,
     pub bots: Vec<Bot>,
     pub bridges: Vec<Bridge>,
     pub clients: Vec<Client>,
@@ -26,7 +238,77 @@ pub struct Projects {
     pub servers: Vec<Server>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Ord)]
+impl Projects {
+    /// Whether the data has no project in any category
+    pub fn is_empty(&self) -> bool {
+        self.bots.is_empty()
+            && self.bridges.is_empty()
+            && self.clients.is_empty()
+            && self.iots.is_empty()
+            && self.others.is_empty()
+            && self.sdks.is_empty()
+            && self.servers.is_empty()
+    }
+
+    /// Sorts the entries of each category by sort order, then by title
+    pub fn sort(&mut self) {
+        sort_entries(&mut self.bots);
+        sort_entries(&mut self.bridges);
+        sort_entries(&mut self.clients);
+        sort_entries(&mut self.iots);
+        sort_entries(&mut self.others);
+        sort_entries(&mut self.sdks);
+        sort_entries(&mut self.servers);
+    }
+
+    /// Renders the data file in its canonical form: categories and fields in the order of
+    /// the types, and every entry laid out like in the templates
+    pub fn to_toml(&self) -> String {
+        let version = format!("{} = {}\n", migrate::VERSION_KEY, self.schema_version);
+        [
+            vec![version],
+            render_registry(&self.authors, "authors"),
+            render_registry(&self.protocols, "protocols"),
+            render_entries(&self.bots),
+            render_entries(&self.bridges),
+            render_entries(&self.clients),
+            render_entries(&self.iots),
+            render_entries(&self.others),
+            render_entries(&self.sdks),
+            render_entries(&self.servers),
+        ]
+        .into_iter()
+        .flatten()
+        .join("\n")
+    }
+}
+
+/// Entries with an explicit sort order come first
+fn sort_entries<T: ProjectEntry>(entries: &mut [T]) {
+    entries.sort_by(|a, b| {
+        (a.sort_order().is_none(), a.sort_order(), a.title()).cmp(&(
+            b.sort_order().is_none(),
+            b.sort_order(),
+            b.title(),
+        ))
+    });
+}
+
+fn render_registry<T: Serialize>(entries: &BTreeMap<String, T>, key: &str) -> Vec<String> {
+    if entries.is_empty() {
+        vec![]
+    } else {
+        vec![templates::registry(entries, Some(key))]
+    }
+}
+
+fn render_entries<T: ProjectEntry>(entries: &[T]) -> Vec<String> {
+    entries.iter().map(templates::entry).collect()
+}
+
+#[derive(
+    Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Ord,
+)]
 pub enum Maturity {
     Obsolete,
     Alpha,
@@ -44,3 +326,78 @@ impl Display for Maturity {
         }
     }
 }
+
+/// How well a project supports a feature
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SupportLevel {
+    Yes,
+    No,
+    Partial,
+}
+
+impl Display for SupportLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SupportLevel::Yes => write!(f, "yes"),
+            SupportLevel::No => write!(f, "no"),
+            SupportLevel::Partial => write!(f, "partial"),
+        }
+    }
+}
+
+/// Implementation of a Matrix Spec Change proposal by a project
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct MscSupport {
+    /// Number of the proposal, e.g. "MSC3575"
+    #[serde(deserialize_with = "deserialize_msc")]
+    pub msc: String,
+    pub status: MscStatus,
+}
+
+/// Rejects malformed MSC numbers when the data is loaded, they couldn't be looked up in the
+/// MSC index
+fn deserialize_msc<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let msc = String::deserialize(deserializer)?;
+    validate::msc(&msc).map_err(de::Error::custom)?;
+    Ok(msc)
+}
+
+/// How far a project is in implementing an MSC
+#[derive(
+    Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum MscStatus {
+    Planned,
+    InProgress,
+    Partial,
+    Implemented,
+}
+
+impl Display for MscStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MscStatus::Planned => write!(f, "planned"),
+            MscStatus::InProgress => write!(f, "in_progress"),
+            MscStatus::Partial => write!(f, "partial"),
+            MscStatus::Implemented => write!(f, "implemented"),
+        }
+    }
+}
+
+/// Front matter of the MSCs a project implements, nothing if it doesn't give any
+pub fn msc_support_front_matter(msc_support: &[MscSupport]) -> Option<String> {
+    if msc_support.is_empty() {
+        return None;
+    }
+    let mscs = msc_support
+        .iter()
+        .map(|m| format!("    - msc: {}\n      status: {}", m.msc, m.status))
+        .join("\n");
+    Some(format!("msc_support:\n{}", mscs))
+}
+
+fn unversioned() -> u32 {
+    migrate::UNVERSIONED
+}
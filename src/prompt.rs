@@ -0,0 +1,36 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// Asks a yes/no question on the terminal. Answers no when not running interactively.
+pub fn confirm(question: &str) -> bool {
+    if !io::stdin().is_terminal() {
+        return false;
+    }
+
+    print!("{} [y/N] ", question);
+    io::stdout().flush().expect("Unable to flush stdout");
+
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .expect("Unable to read from stdin");
+
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Asks for a line of text, without its trailing newline
+pub fn ask(question: &str) -> String {
+    print!("{} ", question);
+    io::stdout().flush().expect("Unable to flush stdout");
+
+    let mut answer = String::new();
+    let read = io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .expect("Unable to read from stdin");
+    if read == 0 {
+        panic!("Unexpected end of input");
+    }
+
+    answer.trim_end_matches(['\n', '\r']).to_string()
+}
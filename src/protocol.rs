@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+
+use indoc::formatdoc;
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::bridge::{Bridge, Features};
+use crate::comparison::{self, Column};
+use crate::projects::{ProjectEntry, ProjectLink, Projects};
+use crate::validate;
+
+/// Category of the protocol pages in the matrix.org front matter
+pub const CATEGORY: &str = "protocol";
+
+/// Directory the matrix.org pages of the protocols are written to
+pub const DIRECTORY: &str = "protocols";
+
+/// Network that bridges connect Matrix to, in the protocol registry
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct Protocol {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub website: Option<String>,
+    /// Other names bridges use for the network, e.g. "Telegram (puppeting)"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+}
+
+impl Protocol {
+    pub fn filename(id: &str) -> String {
+        format!("{}.mdx", id)
+    }
+
+    pub fn matrixdotorg_url(id: &str) -> String {
+        format!("https://matrix.org/docs/projects/{}/{}", CATEGORY, id)
+    }
+
+    /// Index page of the network, listing the bridges to it and comparing their features
+    pub fn to_markdown(&self, bridges: &[&Bridge]) -> String {
+        let title = &self.name;
+        let bridges = bridges
+            .iter()
+            .copied()
+            .sorted_by(|a, b| a.title.cmp(&b.title))
+            .collect::<Vec<_>>();
+        let comparison = comparison_table(&bridges);
+        let bridges = bridges
+            .iter()
+            .map(|bridge| {
+                format!(
+                    "    - title: {}\n      url: {}\n      maturity: {}",
+                    bridge.title,
+                    bridge.matrixdotorg_url(),
+                    bridge.maturity
+                )
+            })
+            .join("\n");
+
+        let optional_fields = [
+            self.website.as_ref().map(|w| format!("home: {w}")),
+            self.icon.as_ref().map(|i| format!("thumbnail: {i}")),
+        ]
+        .iter()
+        .flatten()
+        .join("\n");
+
+        formatdoc! {"
+            ---
+            layout: protocol
+            title: {title}
+            categories:
+             - {CATEGORY}
+            bridges:
+            {bridges}
+            {optional_fields}
+            ---
+            {comparison}
+        "}
+    }
+}
+
+/// Markdown table of the support of each feature by each bridge, or nothing if no bridge
+/// gives its features
+fn comparison_table(bridges: &[&Bridge]) -> String {
+    if bridges.iter().all(|b| b.features.is_none()) {
+        return String::new();
+    }
+
+    let columns = bridges
+        .iter()
+        .map(|bridge| Column::new(&bridge.title, bridge.features.as_ref()))
+        .collect::<Vec<_>>();
+    comparison::features_table::<Features>(&columns)
+}
+
+/// Links the bridges to the pages of the networks they connect to, once there is a protocol
+/// registry. Returns a warning for each network that isn't in it.
+pub fn resolve(projects: &mut Projects) -> Vec<String> {
+    let mut warnings = vec![];
+
+    for bridge in &mut projects.bridges {
+        bridge.networks.clear();
+        bridge.protocol_links.clear();
+
+        for name in &bridge.bridges {
+            match find(name, &projects.protocols) {
+                Some((id, protocol)) => {
+                    let link = ProjectLink {
+                        category: CATEGORY,
+                        title: protocol.name.clone(),
+                        url: Protocol::matrixdotorg_url(id),
+                    };
+                    // Several names of a bridge can be the same network
+                    if !bridge.protocol_links.contains(&link) {
+                        bridge.networks.push(protocol.name.clone());
+                        bridge.protocol_links.push(link);
+                    }
+                }
+                None => {
+                    bridge.networks.push(name.clone());
+                    if projects.protocols.is_empty() {
+                        continue;
+                    }
+                    let suggestions =
+                        validate::suggestions(name, projects.protocols.keys().cloned());
+                    let mut warning = format!(
+                        "{}/{}: unknown protocol {}",
+                        Bridge::KEY,
+                        bridge.slug(),
+                        name
+                    );
+                    if !suggestions.is_empty() {
+                        warning.push_str(&format!(", did you mean {}?", suggestions.join(" or ")));
+                    }
+                    warnings.push(warning);
+                }
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Bridges to each network of the registry, by protocol id
+pub fn bridges_by_protocol(projects: &Projects) -> BTreeMap<&str, Vec<&Bridge>> {
+    let mut bridges: BTreeMap<&str, Vec<&Bridge>> = projects
+        .protocols
+        .keys()
+        .map(|id| (id.as_str(), vec![]))
+        .collect();
+
+    for bridge in &projects.bridges {
+        let ids = bridge
+            .bridges
+            .iter()
+            .filter_map(|name| find(name, &projects.protocols))
+            .map(|(id, _)| id)
+            .unique();
+        for id in ids {
+            bridges.entry(id).or_default().push(bridge);
+        }
+    }
+
+    bridges
+}
+
+/// Protocol a network name refers to, matched with the ids, names and aliases of the registry
+/// ignoring case
+fn find<'a>(
+    name: &str,
+    protocols: &'a BTreeMap<String, Protocol>,
+) -> Option<(&'a str, &'a Protocol)> {
+    protocols
+        .iter()
+        .find(|(id, protocol)| {
+            id.eq_ignore_ascii_case(name)
+                || protocol.name.eq_ignore_ascii_case(name)
+                || protocol
+                    .aliases
+                    .iter()
+                    .any(|a| a.eq_ignore_ascii_case(name))
+        })
+        .map(|(id, protocol)| (id.as_str(), protocol))
+}
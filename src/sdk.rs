@@ -1,30 +1,174 @@
 use convert_case::{Case, Casing};
-use indoc::formatdoc;
+use indoc::{formatdoc, indoc};
 use itertools::Itertools;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::projects::Author;
+use crate::comparison::{self, Column, FeatureLevels};
+use crate::projects::{
+    self, Author, Maturity, MscStatus, MscSupport, ProjectEntry, ProjectLink, SupportLevel,
+};
+use crate::validate;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// Support of each feature by an SDK, unknown when missing
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, Default)]
+pub struct Features {
+    pub e2ee: Option<SupportLevel>,
+    pub sliding_sync: Option<SupportLevel>,
+    /// Application services, e.g. bridges, can be written with it
+    pub appservice: Option<SupportLevel>,
+}
+
+impl FeatureLevels for Features {
+    fn levels(&self) -> Vec<(&'static str, Option<SupportLevel>)> {
+        let Features {
+            e2ee,
+            sliding_sync,
+            appservice,
+        } = self;
+
+        vec![
+            ("E2EE", *e2ee),
+            ("Sliding sync", *sliding_sync),
+            ("Appservices", *appservice),
+        ]
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct Sdk {
     pub title: String,
+    /// One-line summary of the project
     pub description: String,
     pub authors: Vec<Author>,
+    /// Can be Obsolete, Alpha, Beta or Stable
+    #[schemars(with = "Maturity")]
     pub maturity: String,
     pub language: String,
+    /// Has to be one of https://spdx.org/licenses/
     pub license: String,
     pub repository: Option<String>,
     pub home: Option<String>,
     pub screenshot: Option<String>,
     pub icon: Option<String>,
+    /// Alias of the room to discuss the project
     pub room: Option<String>,
     pub featured: bool,
     pub sort_order: Option<i32>,
+    /// Emoji the TWIM bot uses for the project
+    pub twim_emoji: Option<String>,
+    /// Support of each feature, can be "yes", "no" or "partial"
+    pub features: Option<Features>,
+    /// Languages the SDK can be used from besides its own, e.g. through FFI bindings
+    #[serde(default)]
+    pub bindings: Vec<String>,
+    /// Versions of the Matrix spec the SDK implements, e.g. "v1.11" or "r0.6.1"
+    #[serde(default)]
+    pub spec_versions: Vec<String>,
+    /// Matrix Spec Changes the SDK implements, with status "planned", "in_progress", "partial" or "implemented"
+    #[serde(default)]
+    pub msc_support: Vec<MscSupport>,
+    /// Multi-line description, supports markdown
+    #[schemars(extend("x-multiline" = true))]
     pub full_description: String,
+    /// Bots, bridges, clients, IoT, other projects and servers built on the SDK
+    #[serde(skip)]
+    pub used_by: Vec<ProjectLink>,
 }
 
-impl Sdk {
-    pub fn to_markdown(&self) -> String {
+impl ProjectEntry for Sdk {
+    const CATEGORY: &'static str = "sdk";
+    const KEY: &'static str = "sdks";
+    const DIRECTORY: &'static str = "sdks";
+
+    fn example() -> Self {
+        Sdk {
+            title: "Matrix Elixir SDK".to_string(),
+            description: "A SDK that doesn't really exist but that would be fun".to_string(),
+            authors: vec![
+                Author {
+                    name: "Fred".to_string(),
+                    matrix_id: Some("@fred:server.tld".to_string()),
+                    ..Default::default()
+                },
+                Author {
+                    name: "The Alchemists Gang".to_string(),
+                    matrix_id: None,
+                    ..Default::default()
+                },
+            ],
+            maturity: "Beta".to_string(),
+            language: "Elixir".to_string(),
+            license: "AGPL-3.0-or-later".to_string(),
+            repository: Some("https://git.server.tld/elixir".to_string()),
+            home: Some("https://elixir-sdk-project.tld/sdk".to_string()),
+            screenshot: Some("/data/elixir-sdk-screenshot.png".to_string()),
+            icon: Some("/data/elixir-icon.svg".to_string()),
+            room: Some("#elixir-sdk:server.tld".to_string()),
+            featured: false,
+            sort_order: Some(1),
+            twim_emoji: Some("⚗️".to_string()),
+            features: Some(Features {
+                e2ee: Some(SupportLevel::Yes),
+                sliding_sync: Some(SupportLevel::Partial),
+                appservice: Some(SupportLevel::No),
+            }),
+            bindings: vec!["Erlang".to_string(), "Gleam".to_string()],
+            spec_versions: vec!["v1.11".to_string()],
+            msc_support: vec![MscSupport {
+                msc: "MSC3575".to_string(),
+                status: MscStatus::InProgress,
+            }],
+            full_description: indoc! {"
+                A multi-line description that supports markdown to describe the project extensively.
+
+                For example, the Elixir SDK
+                * supports e2ee
+                * allows to very easily create bots
+            "}
+            .to_string(),
+            used_by: vec![],
+        }
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn authors(&self) -> &[Author] {
+        &self.authors
+    }
+
+    fn authors_mut(&mut self) -> &mut Vec<Author> {
+        &mut self.authors
+    }
+
+    fn license(&self) -> &str {
+        &self.license
+    }
+
+    fn room(&self) -> Option<&str> {
+        self.room.as_deref()
+    }
+
+    fn msc_support(&self) -> &[MscSupport] {
+        &self.msc_support
+    }
+
+    fn sort_order(&self) -> Option<i32> {
+        self.sort_order
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut errors = validate::entry(self);
+        errors.extend(validate::spec_support(
+            &self.spec_versions,
+            &self.msc_support,
+        ));
+        errors
+    }
+
+    fn to_markdown(&self) -> String {
         let layout = match self.icon {
             Some(_) => "projectimage",
             None => "project",
@@ -48,6 +192,13 @@ impl Sdk {
             self.icon.as_ref().map(|i| format!("thumbnail: {i}")),
             self.room.as_ref().map(|r| format!("room: \"{r}\"")),
             self.sort_order.as_ref().map(|o| format!("sort_order: {o}")),
+            ProjectLink::front_matter("used_by", &self.used_by),
+            self.features
+                .as_ref()
+                .and_then(|f| projects::features_front_matter(&f.levels())),
+            projects::front_matter_list("bindings", &self.bindings),
+            projects::front_matter_list("spec_versions", &self.spec_versions),
+            projects::msc_support_front_matter(&self.msc_support),
         ]
         .iter()
         .flatten()
@@ -73,7 +224,36 @@ impl Sdk {
         "}
     }
 
-    pub fn filename(&self) -> String {
+    fn filename(&self) -> String {
         format!("{}.mdx", self.title.to_case(Case::Kebab))
     }
 }
+
+impl Sdk {
+    /// Markdown table comparing the language, bindings and features of the SDKs that give
+    /// their features or bindings, or nothing if none does
+    pub fn comparison_table(sdks: &[Sdk]) -> Option<String> {
+        let sdks = sdks
+            .iter()
+            .filter(|s| s.features.is_some() || !s.bindings.is_empty())
+            .sorted_by(|a, b| a.title.cmp(&b.title))
+            .collect::<Vec<_>>();
+        if sdks.is_empty() {
+            return None;
+        }
+
+        let columns = sdks
+            .iter()
+            .map(|sdk| {
+                let mut column = Column::new(&sdk.title, sdk.features.as_ref());
+                let bindings = Some(sdk.bindings.join(", ")).filter(|_| !sdk.bindings.is_empty());
+                column.before = vec![
+                    ("Language", Some(sdk.language.clone())),
+                    ("Bindings", bindings),
+                ];
+                column
+            })
+            .collect::<Vec<_>>();
+        Some(comparison::features_table(&columns))
+    }
+}
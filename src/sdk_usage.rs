@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+
+use convert_case::{Case, Casing};
+use itertools::Itertools;
+
+use crate::projects::{ProjectEntry, ProjectLink, Projects};
+use crate::sdk::Sdk;
+use crate::validate;
+
+/// Links the projects to the pages of the SDKs they are built on, and the SDKs to the projects
+/// using them. Returns a warning for each name that isn't an SDK of the data.
+pub fn resolve(projects: &mut Projects) -> Vec<String> {
+    let mut warnings = vec![];
+    let mut used_by = vec![vec![]; projects.sdks.len()];
+
+    let sdks = &projects.sdks;
+    link_all(&mut projects.bots, sdks, &mut used_by, &mut warnings);
+    link_all(&mut projects.bridges, sdks, &mut used_by, &mut warnings);
+    link_all(&mut projects.clients, sdks, &mut used_by, &mut warnings);
+    link_all(&mut projects.iots, sdks, &mut used_by, &mut warnings);
+    link_all(&mut projects.others, sdks, &mut used_by, &mut warnings);
+    link_all(&mut projects.servers, sdks, &mut used_by, &mut warnings);
+
+    for (sdk, mut users) in projects.sdks.iter_mut().zip(used_by) {
+        users.sort_by(|a, b| (a.category, &a.title).cmp(&(b.category, &b.title)));
+        users.dedup();
+        sdk.used_by = users;
+    }
+
+    warnings
+}
+
+/// Prints how many projects of each category are built on each SDK, most used first, then
+/// the names that aren't SDKs of the data
+pub fn print_report(projects: &Projects) {
+    let mut unknown: BTreeMap<String, usize> = BTreeMap::new();
+    let users = [
+        names(&projects.bots),
+        names(&projects.bridges),
+        names(&projects.clients),
+        names(&projects.iots),
+        names(&projects.others),
+        names(&projects.servers),
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(|(category, name)| match find(&name, &projects.sdks) {
+        Some(index) => Some((index, category)),
+        None => {
+            *unknown.entry(name).or_default() += 1;
+            None
+        }
+    })
+    .into_group_map();
+
+    let adoption = projects
+        .sdks
+        .iter()
+        .enumerate()
+        .map(|(index, sdk)| (sdk, users.get(&index).cloned().unwrap_or_default()))
+        .sorted_by(|(a, a_users), (b, b_users)| {
+            (b_users.len(), &a.title).cmp(&(a_users.len(), &b.title))
+        });
+
+    for (sdk, categories) in adoption {
+        let counts = categories
+            .iter()
+            .counts()
+            .into_iter()
+            .sorted()
+            .map(|(category, count)| format!("{}: {}", category, count))
+            .join(", ");
+        match categories.len() {
+            0 => println!("{}: unused", sdk.title),
+            total => println!("{}: {} ({})", sdk.title, projects_count(total), counts),
+        }
+    }
+
+    if !unknown.is_empty() {
+        println!("Not in the data:");
+        for (name, count) in unknown {
+            println!("  {}: {}", name, projects_count(count));
+        }
+    }
+}
+
+fn projects_count(count: usize) -> String {
+    match count {
+        1 => "1 project".to_string(),
+        _ => format!("{} projects", count),
+    }
+}
+
+fn link_all<T: ProjectEntry>(
+    entries: &mut [T],
+    sdks: &[Sdk],
+    used_by: &mut [Vec<ProjectLink>],
+    warnings: &mut Vec<String>,
+) {
+    for entry in entries {
+        let link = ProjectLink {
+            category: T::CATEGORY,
+            title: entry.title().to_string(),
+            url: entry.matrixdotorg_url(),
+        };
+
+        let mut sdk_links = vec![];
+        for name in entry.sdk() {
+            match find(name, sdks) {
+                Some(index) => {
+                    let sdk = &sdks[index];
+                    sdk_links.push(ProjectLink {
+                        category: Sdk::CATEGORY,
+                        title: sdk.title.clone(),
+                        url: sdk.matrixdotorg_url(),
+                    });
+                    used_by[index].push(link.clone());
+                }
+                None => warnings.push(unknown::<T>(name, &entry.slug(), sdks)),
+            }
+        }
+        entry.set_sdk_links(sdk_links);
+    }
+}
+
+/// Category of each SDK name of the entries
+fn names<T: ProjectEntry>(entries: &[T]) -> Vec<(&'static str, String)> {
+    entries
+        .iter()
+        .flat_map(|entry| entry.sdk().iter().unique())
+        .map(|name| (T::CATEGORY, name.clone()))
+        .collect()
+}
+
+/// Index of the SDK with the given name, matched with its slug or title
+fn find(name: &str, sdks: &[Sdk]) -> Option<usize> {
+    let slug = name.to_case(Case::Kebab);
+    sdks.iter()
+        .position(|sdk| sdk.slug() == slug || sdk.title.eq_ignore_ascii_case(name))
+}
+
+fn unknown<T: ProjectEntry>(name: &str, slug: &str, sdks: &[Sdk]) -> String {
+    let suggestions = validate::suggestions(name, sdks.iter().map(|sdk| sdk.slug()));
+
+    let mut warning = format!("{}/{}: unknown SDK {}", T::KEY, slug, name);
+    if !suggestions.is_empty() {
+        warning.push_str(&format!(", did you mean {}?", suggestions.join(" or ")));
+    }
+    warning
+}
@@ -1,30 +1,197 @@
 use convert_case::{Case, Casing};
-use indoc::formatdoc;
+use indoc::{formatdoc, indoc};
 use itertools::Itertools;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::projects::Author;
+use crate::comparison::{self, Column, FeatureLevels};
+use crate::projects::{
+    self, Author, Maturity, MscStatus, MscSupport, ProjectEntry, ProjectLink, SupportLevel,
+};
+use crate::validate;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+/// Support of each feature by a homeserver, unknown when missing
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, Default)]
+pub struct Features {
+    pub federation: Option<SupportLevel>,
+    pub sliding_sync: Option<SupportLevel>,
+    /// Load can be spread across several processes or machines, e.g. with workers
+    pub horizontal_scaling: Option<SupportLevel>,
+    /// Login through an identity provider with the SSO flow of the spec
+    pub sso: Option<SupportLevel>,
+    /// Authentication delegated to an OpenID Connect provider
+    pub oidc: Option<SupportLevel>,
+}
+
+impl FeatureLevels for Features {
+    fn levels(&self) -> Vec<(&'static str, Option<SupportLevel>)> {
+        let Features {
+            federation,
+            sliding_sync,
+            horizontal_scaling,
+            sso,
+            oidc,
+        } = self;
+
+        vec![
+            ("Federation", *federation),
+            ("Sliding sync", *sliding_sync),
+            ("Horizontal scaling", *horizontal_scaling),
+            ("SSO", *sso),
+            ("OIDC", *oidc),
+        ]
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct Server {
     pub title: String,
+    /// One-line summary of the project
     pub description: String,
     pub authors: Vec<Author>,
+    /// Can be Obsolete, Alpha, Beta or Stable
+    #[schemars(with = "Maturity")]
     pub maturity: String,
     pub language: String,
+    /// Has to be one of https://spdx.org/licenses/
     pub license: String,
     pub repository: Option<String>,
     pub home: Option<String>,
     pub screenshot: Option<String>,
     pub icon: Option<String>,
+    /// Alias of the room to discuss the project
     pub room: Option<String>,
+    /// SDKs the project is built on, slugs or titles of SDK entries
+    #[serde(default)]
+    pub sdk: Vec<String>,
+    /// Pages of the SDKs of `sdk` that are in the data
+    #[serde(skip)]
+    pub sdk_links: Vec<ProjectLink>,
     pub featured: bool,
     pub sort_order: Option<i32>,
+    /// Emoji the TWIM bot uses for the project
+    pub twim_emoji: Option<String>,
+    /// Support of each feature, can be "yes", "no" or "partial"
+    pub features: Option<Features>,
+    /// Versions of the Matrix spec the server implements, e.g. "v1.11" or "r0.6.1"
+    #[serde(default)]
+    pub spec_versions: Vec<String>,
+    /// Matrix Spec Changes the server implements, with status "planned", "in_progress", "partial" or "implemented"
+    #[serde(default)]
+    pub msc_support: Vec<MscSupport>,
+    /// Multi-line description, supports markdown
+    #[schemars(extend("x-multiline" = true))]
     pub full_description: String,
 }
 
-impl Server {
-    pub fn to_markdown(&self) -> String {
+impl ProjectEntry for Server {
+    const CATEGORY: &'static str = "server";
+    const KEY: &'static str = "servers";
+    const DIRECTORY: &'static str = "servers";
+
+    fn example() -> Self {
+        Server {
+            title: "Oxynapse".to_string(),
+            description: "An attempt to oxidise synapse. Use at your own risk.".to_string(),
+            authors: vec![
+                Author {
+                    name: "Fred".to_string(),
+                    matrix_id: Some("@fred:server.tld".to_string()),
+                    ..Default::default()
+                },
+                Author {
+                    name: "Water & Iron LLC".to_string(),
+                    matrix_id: None,
+                    ..Default::default()
+                },
+            ],
+            maturity: "Alpha".to_string(),
+            language: "Rust".to_string(),
+            license: "Apache-2.0".to_string(),
+            repository: Some("https://git.server.tld/oxynapse".to_string()),
+            home: Some("https://server.tld/oxynapse".to_string()),
+            screenshot: Some("/data/oxynapse-screenshot.png".to_string()),
+            icon: Some("/data/oxynapse-icon.svg".to_string()),
+            room: Some("#oxynapse:server.tld".to_string()),
+            sdk: vec!["ruma".to_string()],
+            sdk_links: vec![],
+            featured: false,
+            sort_order: Some(1),
+            twim_emoji: Some("🦀".to_string()),
+            features: Some(Features {
+                federation: Some(SupportLevel::Yes),
+                sliding_sync: Some(SupportLevel::Partial),
+                horizontal_scaling: Some(SupportLevel::No),
+                sso: Some(SupportLevel::Yes),
+                oidc: Some(SupportLevel::No),
+            }),
+            spec_versions: vec!["v1.10".to_string(), "v1.11".to_string()],
+            msc_support: vec![
+                MscSupport {
+                    msc: "MSC3575".to_string(),
+                    status: MscStatus::Partial,
+                },
+                MscSupport {
+                    msc: "MSC4186".to_string(),
+                    status: MscStatus::Planned,
+                },
+            ],
+            full_description: indoc! {"
+                A multi-line description that supports markdown to describe the project extensively.
+
+                Oxynapse is about oxdising all of Synapse. This is still early days, and I'm just
+                a silly documentation example, don't take it for an easter egg or hidden announcement.
+            "}
+            .to_string(),
+        }
+    }
+
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn authors(&self) -> &[Author] {
+        &self.authors
+    }
+
+    fn authors_mut(&mut self) -> &mut Vec<Author> {
+        &mut self.authors
+    }
+
+    fn license(&self) -> &str {
+        &self.license
+    }
+
+    fn room(&self) -> Option<&str> {
+        self.room.as_deref()
+    }
+
+    fn sdk(&self) -> &[String] {
+        &self.sdk
+    }
+
+    fn set_sdk_links(&mut self, links: Vec<ProjectLink>) {
+        self.sdk_links = links;
+    }
+
+    fn msc_support(&self) -> &[MscSupport] {
+        &self.msc_support
+    }
+
+    fn sort_order(&self) -> Option<i32> {
+        self.sort_order
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut errors = validate::entry(self);
+        errors.extend(validate::spec_support(
+            &self.spec_versions,
+            &self.msc_support,
+        ));
+        errors
+    }
+
+    fn to_markdown(&self) -> String {
         let layout = match self.icon {
             Some(_) => "projectimage",
             None => "project",
@@ -48,6 +215,13 @@ impl Server {
             self.icon.as_ref().map(|i| format!("thumbnail: {i}")),
             self.room.as_ref().map(|r| format!("room: \"{r}\"")),
             self.sort_order.as_ref().map(|o| format!("sort_order: {o}")),
+            projects::front_matter_list("sdk", &self.sdk),
+            ProjectLink::front_matter("sdk_links", &self.sdk_links),
+            self.features
+                .as_ref()
+                .and_then(|f| projects::features_front_matter(&f.levels())),
+            projects::front_matter_list("spec_versions", &self.spec_versions),
+            projects::msc_support_front_matter(&self.msc_support),
         ]
         .iter()
         .flatten()
@@ -73,7 +247,34 @@ impl Server {
         "}
     }
 
-    pub fn filename(&self) -> String {
+    fn filename(&self) -> String {
         format!("{}.mdx", self.title.to_case(Case::Kebab))
     }
 }
+
+impl Server {
+    /// Markdown table comparing the features and spec versions of the servers that give
+    /// them, or nothing if none does
+    pub fn comparison_table(servers: &[Server]) -> Option<String> {
+        let servers = servers
+            .iter()
+            .filter(|s| s.features.is_some() || !s.spec_versions.is_empty())
+            .sorted_by(|a, b| a.title.cmp(&b.title))
+            .collect::<Vec<_>>();
+        if servers.is_empty() {
+            return None;
+        }
+
+        let columns = servers
+            .iter()
+            .map(|server| {
+                let mut column = Column::new(&server.title, server.features.as_ref());
+                let spec_versions = Some(server.spec_versions.join(", "))
+                    .filter(|_| !server.spec_versions.is_empty());
+                column.after.push(("Spec versions", spec_versions));
+                column
+            })
+            .collect::<Vec<_>>();
+        Some(comparison::features_table(&columns))
+    }
+}
@@ -0,0 +1,85 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+/// Optional configuration of squiddy itself, read from `squiddy.toml`
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub sections: Sections,
+    /// Data directories merged on top of `./data`, each one overriding the previous ones
+    pub overlays: Vec<String>,
+}
+
+/// The twim-config section each category of projects is reported in
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Sections {
+    pub bots: SectionSettings,
+    pub bridges: SectionSettings,
+    pub clients: SectionSettings,
+    pub iots: SectionSettings,
+    pub others: SectionSettings,
+    pub sdks: SectionSettings,
+    pub servers: SectionSettings,
+}
+
+/// What to create in twim-config if the section doesn't exist yet
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SectionSettings {
+    pub name: String,
+    pub emoji: String,
+    pub title: String,
+    pub order: u32,
+}
+
+impl Settings {
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        match fs::read(path) {
+            Ok(file) => toml::from_slice(&file)
+                .unwrap_or_else(|e| panic!("Unable to parse {}: {}", path.display(), e)),
+            Err(_) => Settings::default(),
+        }
+    }
+}
+
+impl Sections {
+    pub fn iter(&self) -> impl Iterator<Item = &SectionSettings> {
+        [
+            &self.bots,
+            &self.bridges,
+            &self.clients,
+            &self.iots,
+            &self.others,
+            &self.sdks,
+            &self.servers,
+        ]
+        .into_iter()
+    }
+}
+
+impl Default for Sections {
+    fn default() -> Self {
+        Sections {
+            bots: SectionSettings::new("bots", "🤖", "Bots", 50),
+            bridges: SectionSettings::new("bridges", "🌉", "Bridges", 40),
+            clients: SectionSettings::new("clients", "📱", "Clients", 20),
+            iots: SectionSettings::new("iot", "🏠", "Internet of Things", 70),
+            others: SectionSettings::new("other", "🧩", "Others", 80),
+            sdks: SectionSettings::new("sdks", "🧰", "SDKs and Frameworks", 30),
+            servers: SectionSettings::new("servers", "🖥️", "Homeservers", 10),
+        }
+    }
+}
+
+impl SectionSettings {
+    fn new(name: &str, emoji: &str, title: &str, order: u32) -> Self {
+        SectionSettings {
+            name: name.to_string(),
+            emoji: emoji.to_string(),
+            title: title.to_string(),
+            order,
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use schemars::Schema;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use toml_edit::{ArrayOfTables, DocumentMut, Item, Table, Value};
+
+use crate::projects::ProjectEntry;
+
+/// Renders the example entry of a category as a template contributors can copy in the data
+/// file, with a comment on the fields that are optional or need explaining
+pub fn template<T: ProjectEntry>() -> String {
+    render(&T::example(), true)
+}
+
+/// Renders an entry the way it is written in the data file, under its `[[category]]` header
+pub fn entry<T: ProjectEntry>(entry: &T) -> String {
+    render(entry, false)
+}
+
+/// Renders an entry on its own, the way it is written in a file of its category directory
+pub fn standalone_entry<T: ProjectEntry>(entry: &T) -> String {
+    DocumentMut::from(entry_table(entry, false)).to_string()
+}
+
+/// Renders a registry (e.g. the author registry), as a `[<id>]` table per entry, under `key`
+/// if given
+pub fn registry<T: Serialize>(entries: &BTreeMap<String, T>, key: Option<&str>) -> String {
+    let mut table = Table::new();
+    table.set_implicit(true);
+    for (id, details) in entries {
+        let entry = toml_edit::ser::to_document(details)
+            .expect("Unable to serialize registry entry")
+            .as_table()
+            .clone();
+        table.insert(id, Item::Table(entry));
+    }
+
+    let mut document = DocumentMut::new();
+    match key {
+        Some(key) => {
+            document.insert(key, Item::Table(table));
+        }
+        None => *document.as_table_mut() = table,
+    }
+    document.to_string()
+}
+
+fn render<T: ProjectEntry>(entry: &T, comments: bool) -> String {
+    let mut entries = ArrayOfTables::new();
+    entries.push(entry_table(entry, comments));
+
+    let mut document = DocumentMut::new();
+    document.insert(T::KEY, Item::ArrayOfTables(entries));
+    document.to_string()
+}
+
+fn entry_table<T: ProjectEntry>(entry: &T, comments: bool) -> Table {
+    let schema = schemars::schema_for!(T);
+    let mut table = toml_edit::ser::to_document(entry)
+        .expect("Unable to serialize entry")
+        .as_table()
+        .clone();
+    annotate(&mut table, &schema, comments);
+    table
+}
+
+/// Lays the fields out the way they are written by hand, and comments them from the schema
+/// if asked to
+fn annotate(table: &mut Table, schema: &Schema, comments: bool) {
+    let properties = schema.get("properties").and_then(JsonValue::as_object);
+    let required = schema
+        .get("required")
+        .and_then(JsonValue::as_array)
+        .cloned()
+        .unwrap_or_default();
+
+    for (key, item) in table.iter_mut() {
+        let property = properties.and_then(|p| p.get(key.get()));
+        let comment =
+            comment(property, required.iter().any(|r| r == key.get())).filter(|_| comments);
+
+        // Multi-line fields are always written as blocks ending with a single newline
+        if property.is_some_and(|p| p.get("x-multiline").is_some()) {
+            if let Some(text) = item.as_str() {
+                *item = toml_edit::value(normalize_multiline(text));
+            }
+        }
+
+        match item {
+            // Nested structs are written as dotted keys, e.g. `features.e2ee = "yes"`
+            Item::Value(Value::InlineTable(inline)) => {
+                let mut nested = inline.clone().into_table();
+                nested.set_dotted(true);
+                let first = nested.iter_mut().next().and_then(|(_, v)| v.as_value_mut());
+                if let (Some(comment), Some(value)) = (comment, first) {
+                    value.decor_mut().set_suffix(format!(" # {}", comment));
+                }
+                *item = Item::Table(nested);
+            }
+            // Lists of structs are written as tables following the entry, e.g. `[[bots.authors]]`
+            Item::Value(Value::Array(array))
+                if !array.is_empty() && array.iter().all(Value::is_inline_table) =>
+            {
+                let mut tables = ArrayOfTables::new();
+                for value in array.iter() {
+                    if let Some(inline) = value.as_inline_table() {
+                        tables.push(inline.clone().into_table());
+                    }
+                }
+                *item = Item::ArrayOfTables(tables);
+            }
+            // Multi-line strings end with a closing delimiter on its own line, they are
+            // left without a comment
+            Item::Value(value) if value.as_str().is_some_and(|s| s.contains('\n')) => {}
+            Item::Value(value) => {
+                if let Some(comment) = comment {
+                    value.decor_mut().set_suffix(format!(" # {}", comment));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Comment of a field from its description in the schema, if any, and whether it's required
+fn comment(property: Option<&JsonValue>, required: bool) -> Option<String> {
+    let description = property
+        .and_then(|p| p.get("description"))
+        .and_then(JsonValue::as_str);
+
+    match (description, required) {
+        (Some(description), true) => Some(description.to_string()),
+        (Some(description), false) => Some(format!("{} (optional)", description)),
+        (None, true) => None,
+        (None, false) => Some("optional".to_string()),
+    }
+}
+
+/// Trims trailing whitespace and blank lines around the text, and ends it with a newline
+fn normalize_multiline(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    let first = lines
+        .iter()
+        .position(|l| !l.is_empty())
+        .unwrap_or(lines.len());
+    let last = lines
+        .iter()
+        .rposition(|l| !l.is_empty())
+        .map_or(first, |i| i + 1);
+
+    format!("{}\n", lines[first..last].join("\n"))
+}
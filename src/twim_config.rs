@@ -1,8 +1,11 @@
 use convert_case::{Case, Casing};
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use toml_edit::{ArrayOfTables, DocumentMut, Item, Table, Value};
 
 use crate::{
     bot::Bot, bridge::Bridge, client::Client, iot::Iot, other::Other, sdk::Sdk, server::Server,
+    settings::SectionSettings,
 };
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, Default)]
@@ -40,10 +43,190 @@ pub struct Config {
     pub projects: Vec<Project>,
 }
 
-impl From<&Bot> for Project {
-    fn from(bot: &Bot) -> Self {
+impl Project {
+    pub fn has_placeholder_emoji(&self) -> bool {
+        self.emoji.ends_with('?')
+    }
+}
+
+/// Emoji given to projects that don't have one yet, the TWIM bot can't use it
+fn placeholder_emoji(title: &str) -> String {
+    format!("{}?", title.to_case(Case::Kebab))
+}
+
+impl From<&SectionSettings> for Section {
+    fn from(section: &SectionSettings) -> Self {
+        Section {
+            emoji: section.emoji.clone(),
+            name: section.name.clone(),
+            title: section.title.clone(),
+            order: section.order,
+            usual_reporters: vec![],
+        }
+    }
+}
+
+impl Config {
+    /// Emojis shared by several sections or projects, with the names of the ones using them
+    pub fn emoji_collisions(&self) -> Vec<(String, Vec<String>)> {
+        let sections = self.sections.iter().map(|s| (&s.emoji, &s.name));
+        let projects = self
+            .projects
+            .iter()
+            .filter(|p| !p.has_placeholder_emoji())
+            .map(|p| (&p.emoji, &p.name));
+
+        sections
+            .chain(projects)
+            .into_group_map()
+            .into_iter()
+            .filter(|(_, names)| names.len() > 1)
+            .map(|(emoji, names)| (emoji.clone(), names.into_iter().cloned().collect()))
+            .sorted()
+            .collect()
+    }
+
+    /// Projects the TWIM bot can't use yet because they don't have a real emoji
+    pub fn projects_with_placeholder_emoji(&self) -> Vec<&Project> {
+        self.projects
+            .iter()
+            .filter(|p| p.has_placeholder_emoji())
+            .collect()
+    }
+
+    /// Sections referenced by projects but missing from the config, without duplicates
+    pub fn missing_sections(&self) -> Vec<String> {
+        self.projects
+            .iter()
+            .map(|p| &p.default_section)
+            .filter(|name| !self.sections.iter().any(|s| &&s.name == name))
+            .unique()
+            .cloned()
+            .collect()
+    }
+
+    /// Replaces the project with the same title, or adds it if there's none. Returns whether
+    /// the project already existed.
+    pub fn upsert(&mut self, mut project: Project) -> bool {
+        match self.projects.iter_mut().find(|p| p.title == project.title) {
+            Some(existing) => {
+                println!("Found {} in data and twim-config", project.title);
+                // Entries without a `twim_emoji` keep the one an editor set in twim-config
+                if project.has_placeholder_emoji() && !existing.has_placeholder_emoji() {
+                    project.emoji = existing.emoji.clone();
+                }
+                *existing = project;
+                true
+            }
+            None => {
+                println!("Didn't find {} in twim-config, adding", project.title);
+                self.projects.push(project);
+                false
+            }
+        }
+    }
+
+    /// Sorts projects by section then by name, so that the output doesn't depend on the
+    /// order of entries in the data file
+    pub fn sort_projects(&mut self) {
+        self.projects.sort_by(|a, b| {
+            a.default_section
+                .cmp(&b.default_section)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+    }
+
+    /// Renders the sections and projects into the original config file. Tables that didn't
+    /// change are kept as they are, and only the values that changed are replaced in the
+    /// others, so comments and formatting of the hand-edited file survive.
+    pub fn to_document(&self, original: &str) -> String {
+        let mut document: DocumentMut = original.parse().expect("Unable to parse twim-config file");
+
+        update_array(&mut document, "sections", "name", &self.sections);
+        update_array(&mut document, "projects", "title", &self.projects);
+
+        document.to_string()
+    }
+}
+
+/// Replaces the array of tables `key` by `entries`, reusing the existing table that has
+/// the same `id_key` for each entry
+fn update_array<T: Serialize>(document: &mut DocumentMut, key: &str, id_key: &str, entries: &[T]) {
+    let mut existing: Vec<Table> = document
+        .get(key)
+        .and_then(Item::as_array_of_tables)
+        .map(|tables| tables.iter().cloned().collect())
+        .unwrap_or_default();
+    // Tables are rendered by position in the document, keep the entries where they were
+    let position = existing.iter().find_map(Table::position);
+
+    let mut tables = ArrayOfTables::new();
+    for entry in entries {
+        let updated = toml_edit::ser::to_document(entry)
+            .unwrap_or_else(|e| panic!("Unable to serialize twim-config {}: {}", key, e))
+            .as_table()
+            .clone();
+        let id = updated.get(id_key).and_then(Item::as_str);
+
+        let mut table = match existing
+            .iter()
+            .position(|t| t.get(id_key).and_then(Item::as_str) == id)
+        {
+            Some(index) => {
+                let mut table = existing.remove(index);
+                update_table(&mut table, &updated);
+                table
+            }
+            None => updated,
+        };
+        table.set_position(position);
+        tables.push(table);
+    }
+    document[key] = Item::ArrayOfTables(tables);
+}
+
+/// Replaces the values of `table` that differ from `updated`, keeping their comments
+fn update_table(table: &mut Table, updated: &Table) {
+    for (key, item) in updated.iter() {
+        let new_value = match item.as_value() {
+            Some(value) => value,
+            None => continue,
+        };
+
+        match table.get_mut(key).and_then(Item::as_value_mut) {
+            Some(old_value) if same_value(old_value, new_value) => {}
+            Some(old_value) => {
+                let decor = old_value.decor().clone();
+                *old_value = new_value.clone();
+                *old_value.decor_mut() = decor;
+            }
+            None => {
+                table.insert(key, item.clone());
+            }
+        }
+    }
+}
+
+/// Compares two values regardless of how they are formatted
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => a.value() == b.value(),
+        (Value::Integer(a), Value::Integer(b)) => a.value() == b.value(),
+        (Value::Boolean(a), Value::Boolean(b)) => a.value() == b.value(),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| same_value(a, b))
+        }
+        _ => a.to_string().trim() == b.to_string().trim(),
+    }
+}
+
+impl From<(&Bot, &SectionSettings)> for Project {
+    fn from((bot, section): (&Bot, &SectionSettings)) -> Self {
         Project {
-            emoji: format!("{}?", bot.title.to_case(Case::Kebab)),
+            emoji: bot
+                .twim_emoji
+                .clone()
+                .unwrap_or_else(|| placeholder_emoji(&bot.title)),
             name: bot.title.to_case(Case::Kebab),
             title: bot.title.clone(),
             description: bot.description.clone(),
@@ -52,7 +235,7 @@ impl From<&Bot> for Project {
                 .clone()
                 .or_else(|| bot.repository.clone())
                 .unwrap_or_default(),
-            default_section: "bots".to_string(),
+            default_section: section.name.clone(),
             usual_reporters: bot
                 .authors
                 .iter()
@@ -62,10 +245,13 @@ impl From<&Bot> for Project {
     }
 }
 
-impl From<&Bridge> for Project {
-    fn from(bridge: &Bridge) -> Self {
+impl From<(&Bridge, &SectionSettings)> for Project {
+    fn from((bridge, section): (&Bridge, &SectionSettings)) -> Self {
         Project {
-            emoji: format!("{}?", bridge.title.to_case(Case::Kebab)),
+            emoji: bridge
+                .twim_emoji
+                .clone()
+                .unwrap_or_else(|| placeholder_emoji(&bridge.title)),
             name: bridge.title.to_case(Case::Kebab),
             title: bridge.title.clone(),
             description: bridge.description.clone(),
@@ -74,7 +260,7 @@ impl From<&Bridge> for Project {
                 .clone()
                 .or_else(|| bridge.repository.clone())
                 .unwrap_or_default(),
-            default_section: "bridges".to_string(),
+            default_section: section.name.clone(),
             usual_reporters: bridge
                 .authors
                 .iter()
@@ -84,10 +270,13 @@ impl From<&Bridge> for Project {
     }
 }
 
-impl From<&Client> for Project {
-    fn from(client: &Client) -> Self {
+impl From<(&Client, &SectionSettings)> for Project {
+    fn from((client, section): (&Client, &SectionSettings)) -> Self {
         Project {
-            emoji: format!("{}?", client.title.to_case(Case::Kebab)),
+            emoji: client
+                .twim_emoji
+                .clone()
+                .unwrap_or_else(|| placeholder_emoji(&client.title)),
             name: client.title.to_case(Case::Kebab),
             title: client.title.clone(),
             description: client.description.clone(),
@@ -96,7 +285,7 @@ impl From<&Client> for Project {
                 .clone()
                 .or_else(|| client.repository.clone())
                 .unwrap_or_default(),
-            default_section: "clients".to_string(),
+            default_section: section.name.clone(),
             usual_reporters: client
                 .authors
                 .iter()
@@ -106,10 +295,13 @@ impl From<&Client> for Project {
     }
 }
 
-impl From<&Iot> for Project {
-    fn from(iot: &Iot) -> Self {
+impl From<(&Iot, &SectionSettings)> for Project {
+    fn from((iot, section): (&Iot, &SectionSettings)) -> Self {
         Project {
-            emoji: format!("{}?", iot.title.to_case(Case::Kebab)),
+            emoji: iot
+                .twim_emoji
+                .clone()
+                .unwrap_or_else(|| placeholder_emoji(&iot.title)),
             name: iot.title.to_case(Case::Kebab),
             title: iot.title.clone(),
             description: iot.description.clone(),
@@ -118,7 +310,7 @@ impl From<&Iot> for Project {
                 .clone()
                 .or_else(|| iot.repository.clone())
                 .unwrap_or_default(),
-            default_section: "iot".to_string(),
+            default_section: section.name.clone(),
             usual_reporters: iot
                 .authors
                 .iter()
@@ -128,10 +320,13 @@ impl From<&Iot> for Project {
     }
 }
 
-impl From<&Other> for Project {
-    fn from(other: &Other) -> Self {
+impl From<(&Other, &SectionSettings)> for Project {
+    fn from((other, section): (&Other, &SectionSettings)) -> Self {
         Project {
-            emoji: format!("{}?", other.title.to_case(Case::Kebab)),
+            emoji: other
+                .twim_emoji
+                .clone()
+                .unwrap_or_else(|| placeholder_emoji(&other.title)),
             name: other.title.to_case(Case::Kebab),
             title: other.title.clone(),
             description: other.description.clone(),
@@ -140,7 +335,7 @@ impl From<&Other> for Project {
                 .clone()
                 .or_else(|| other.repository.clone())
                 .unwrap_or_default(),
-            default_section: "other".to_string(),
+            default_section: section.name.clone(),
             usual_reporters: other
                 .authors
                 .iter()
@@ -150,10 +345,13 @@ impl From<&Other> for Project {
     }
 }
 
-impl From<&Sdk> for Project {
-    fn from(sdk: &Sdk) -> Self {
+impl From<(&Sdk, &SectionSettings)> for Project {
+    fn from((sdk, section): (&Sdk, &SectionSettings)) -> Self {
         Project {
-            emoji: format!("{}?", sdk.title.to_case(Case::Kebab)),
+            emoji: sdk
+                .twim_emoji
+                .clone()
+                .unwrap_or_else(|| placeholder_emoji(&sdk.title)),
             name: sdk.title.to_case(Case::Kebab),
             title: sdk.title.clone(),
             description: sdk.description.clone(),
@@ -162,7 +360,7 @@ impl From<&Sdk> for Project {
                 .clone()
                 .or_else(|| sdk.repository.clone())
                 .unwrap_or_default(),
-            default_section: "sdks".to_string(),
+            default_section: section.name.clone(),
             usual_reporters: sdk
                 .authors
                 .iter()
@@ -172,10 +370,13 @@ impl From<&Sdk> for Project {
     }
 }
 
-impl From<&Server> for Project {
-    fn from(server: &Server) -> Self {
+impl From<(&Server, &SectionSettings)> for Project {
+    fn from((server, section): (&Server, &SectionSettings)) -> Self {
         Project {
-            emoji: format!("{}?", server.title.to_case(Case::Kebab)),
+            emoji: server
+                .twim_emoji
+                .clone()
+                .unwrap_or_else(|| placeholder_emoji(&server.title)),
             name: server.title.to_case(Case::Kebab),
             title: server.title.clone(),
             description: server.description.clone(),
@@ -184,7 +385,7 @@ impl From<&Server> for Project {
                 .clone()
                 .or_else(|| server.repository.clone())
                 .unwrap_or_default(),
-            default_section: "servers".to_string(),
+            default_section: section.name.clone(),
             usual_reporters: server
                 .authors
                 .iter()
@@ -193,3 +394,78 @@ impl From<&Server> for Project {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    const CONFIG: &str = indoc! {r#"
+        bot_user_id = "@twim:matrix.org"
+        reporting_room_id = "!reporting:matrix.org"
+        admin_room_id = "!admin:matrix.org"
+        notice_emoji = "📢"
+        image_markdown = ""
+        video_markdown = ""
+        verbs = ["says"]
+        update_config_command = "!update"
+        editors = []
+
+        # Projects, kept in sync by squiddy
+        [[projects]]
+        emoji = "🤖" # picked by the editors
+        name = "botty"
+        title = "Botty"
+        description = "Beeps"
+        website = "https://botty.tld"
+        default_section = "bots"
+        usual_reporters = []
+
+        # Sections come last in this file
+        [[sections]]
+        emoji = "🤖"
+        name = "bots"
+        title = "Bots"
+        order = 1
+        usual_reporters = []
+    "#};
+
+    fn config() -> Config {
+        toml::from_str(CONFIG).unwrap()
+    }
+
+    #[test]
+    fn keeps_an_unchanged_config_as_it_is() {
+        assert_eq!(config().to_document(CONFIG), CONFIG);
+    }
+
+    #[test]
+    fn keeps_the_comment_of_a_changed_value() {
+        let mut config = config();
+        config.projects[0].emoji = "🐙".to_string();
+
+        let document = config.to_document(CONFIG);
+        assert!(document.contains("emoji = \"🐙\" # picked by the editors\n"));
+        assert_eq!(document.replace("🐙", "🤖"), CONFIG);
+    }
+
+    #[test]
+    fn adds_projects_in_the_projects_block() {
+        let mut config = config();
+        config.projects.push(Project {
+            emoji: "🌉".to_string(),
+            name: "bridgeridoo".to_string(),
+            title: "Bridgeridoo".to_string(),
+            default_section: "bridges".to_string(),
+            ..Default::default()
+        });
+
+        let document = config.to_document(CONFIG);
+        let botty = document.find("title = \"Botty\"").unwrap();
+        let bridgeridoo = document.find("title = \"Bridgeridoo\"").unwrap();
+        let sections = document.find("[[sections]]").unwrap();
+        assert!(botty < bridgeridoo && bridgeridoo < sections);
+        assert!(document.contains("# Sections come last in this file\n[[sections]]"));
+    }
+}
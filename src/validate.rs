@@ -0,0 +1,174 @@
+use convert_case::{Case, Casing};
+use itertools::Itertools;
+
+use crate::projects::{Author, MscSupport, ProjectEntry, SupportLevel};
+
+/// Most candidates suggested for an unknown name
+const MAX_SUGGESTIONS: usize = 3;
+
+pub fn license(license: &str) -> Result<(), String> {
+    spdx::Expression::parse(license).map(|_| ()).map_err(|_| {
+        format!(
+            "{} is not a valid SPDX license expression, see https://spdx.org/licenses/",
+            license
+        )
+    })
+}
+
+pub fn matrix_id(matrix_id: &str) -> Result<(), String> {
+    sigil_and_server(matrix_id, '@').map_err(|_| {
+        format!(
+            "{} is not a valid Matrix ID, e.g. @alice:server.tld",
+            matrix_id
+        )
+    })
+}
+
+pub fn room_alias(alias: &str) -> Result<(), String> {
+    sigil_and_server(alias, '#')
+        .map_err(|_| format!("{} is not a valid room alias, e.g. #room:server.tld", alias))
+}
+
+/// Levels are parsed like the data files so that they can't drift from `SupportLevel`
+pub fn feature_level(level: &str) -> Result<(), String> {
+    serde_json::from_value::<SupportLevel>(level.into())
+        .map(|_| ())
+        .map_err(|_| {
+            format!(
+                "{} is not a valid feature level, can be {}",
+                level,
+                feature_levels().join(", ")
+            )
+        })
+}
+
+/// Levels a project can support a feature with, as written in the data files
+fn feature_levels() -> Vec<String> {
+    schemars::schema_for!(SupportLevel)
+        .get("enum")
+        .and_then(serde_json::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|level| level.as_str().map(str::to_string))
+        .collect()
+}
+
+/// Spec versions are written like in the `/versions` endpoint, e.g. `v1.11` or `r0.6.1`
+pub fn spec_version(version: &str) -> Result<(), String> {
+    // Versions since v1.1 have two numbers, the older r0 ones have three
+    let numbers = match (version.strip_prefix('v'), version.strip_prefix('r')) {
+        (Some(numbers), _) => Some((numbers, 2)),
+        (_, Some(numbers)) => Some((numbers, 3)),
+        _ => None,
+    };
+    let valid = numbers.is_some_and(|(numbers, count)| {
+        let numbers = numbers.split('.').collect::<Vec<_>>();
+        numbers.len() == count
+            && numbers
+                .iter()
+                .all(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+    });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} is not a valid spec version, e.g. v1.11 or r0.6.1",
+            version
+        ))
+    }
+}
+
+/// Leading zeros are rejected so that each MSC has a single spelling, e.g. in the MSC index
+pub fn msc(msc: &str) -> Result<(), String> {
+    match msc.strip_prefix("MSC") {
+        Some(number)
+            if number.chars().all(|c| c.is_ascii_digit())
+                && !number.starts_with('0')
+                && number.parse::<u32>().is_ok() =>
+        {
+            Ok(())
+        }
+        _ => Err(format!("{} is not a valid MSC, e.g. MSC3575", msc)),
+    }
+}
+
+/// Checks the spec versions and MSCs an entry implements, the format of the MSCs is checked
+/// when they are loaded
+pub fn spec_support(spec_versions: &[String], msc_support: &[MscSupport]) -> Vec<String> {
+    let mut errors = spec_versions
+        .iter()
+        .filter_map(|v| spec_version(v).err())
+        .collect::<Vec<_>>();
+    errors.extend(
+        msc_support
+            .iter()
+            .map(|m| &m.msc)
+            .duplicates()
+            .map(|m| format!("{} is listed several times", m)),
+    );
+    errors
+}
+
+/// Checks a single field, identified by its dotted path in the entry (e.g. `authors.matrix_id`)
+pub fn field(path: &str, value: &str) -> Result<(), String> {
+    match path {
+        "license" => license(value),
+        "room" => room_alias(value),
+        "authors.matrix_id" | "instance" => matrix_id(value),
+        "spec_versions" => spec_version(value),
+        "msc_support.msc" => msc(value),
+        _ if path.starts_with("features.") => feature_level(value),
+        _ => Ok(()),
+    }
+}
+
+/// Checks what every category has in common
+pub fn entry<T: ProjectEntry>(entry: &T) -> Vec<String> {
+    let mut errors = vec![];
+
+    if let Err(e) = license(entry.license()) {
+        errors.push(e);
+    }
+    if let Some(Err(e)) = entry.room().map(room_alias) {
+        errors.push(e);
+    }
+    errors.extend(
+        entry
+            .authors()
+            .iter()
+            .filter_map(|a: &Author| a.matrix_id.as_deref())
+            .filter_map(|id| matrix_id(id).err()),
+    );
+
+    errors
+}
+
+/// Candidates close to a name that refers to nothing, closest first. Names are compared in
+/// kebab case, the way slugs and ids are written.
+pub fn suggestions(name: &str, candidates: impl Iterator<Item = String>) -> Vec<String> {
+    let name = name.to_case(Case::Kebab);
+    candidates
+        .map(|candidate| (strsim::levenshtein(&name, &candidate), candidate))
+        .filter(|(distance, candidate)| {
+            *distance <= (name.len() / 3).max(2)
+                || candidate.contains(&name)
+                || name.contains(candidate.as_str())
+        })
+        .sorted()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+fn sigil_and_server(id: &str, sigil: char) -> Result<(), ()> {
+    let (localpart, server) = id
+        .strip_prefix(sigil)
+        .and_then(|rest| rest.split_once(':'))
+        .ok_or(())?;
+
+    if localpart.is_empty() || server.is_empty() || id.contains(char::is_whitespace) {
+        return Err(());
+    }
+    Ok(())
+}